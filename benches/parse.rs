@@ -0,0 +1,35 @@
+//! Parses `tests/64KB.json` scaled up to a document of several megabytes.
+//!
+//! Run with `cargo bench --bench parse`, the number of copies can be passed as an argument.
+
+use std::fs;
+use std::time::{Duration, Instant};
+use json_editor::json::to_object;
+
+const DEFAULT_COPIES: usize = 256;
+const RUNS: usize = 5;
+
+fn best_of<T>(runs: usize, mut f: impl FnMut() -> T) -> Duration {
+    (0..runs).map(|_| {
+        let start = Instant::now();
+        std::hint::black_box(f());
+        start.elapsed()
+    }).min().unwrap_or_default()
+}
+
+fn main() {
+    let copies = std::env::args().skip(1)
+        .find_map(|x| x.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_COPIES);
+
+    let sample = fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/64KB.json"))
+        .expect("Could not read tests/64KB.json");
+    let elements = sample.trim().trim_start_matches('[').trim_end_matches(']').trim();
+    let document = format!("[{}]", vec![elements; copies].join(","));
+    let megabytes = document.len() as f64 / (1024.0 * 1024.0);
+
+    println!("document: {} copies of 64KB.json, {:.1} MB", copies, megabytes);
+
+    let parse = best_of(RUNS, || to_object(&document).expect("Benchmark document is valid"));
+    println!("to_object:          {:>8.1} ms  {:>8.1} MB/s", parse.as_secs_f64() * 1000.0, megabytes / parse.as_secs_f64());
+}
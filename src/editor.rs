@@ -1,26 +1,160 @@
+use std::collections::HashMap;
 use std::fs;
-use std::hash::Hasher;
+use std::io::Read;
+use std::ops::Range;
 use std::path::PathBuf;
-use egui::{Label, Sense, Ui};
+use std::sync::{mpsc, Arc};
+use egui::{Color32, Label, Sense, Ui};
 use egui::scroll_area::ScrollBarVisibility::VisibleWhenNeeded;
 use egui_modal::Modal;
-use json_editor::json::{Array, to_object};
+use json_editor::json::{Array, Recovered, lines, to_object_recovering_with, to_object_recovering_with_progress, to_object_with};
+use json_editor::json::options::{Dialect, ParseOptions};
+use json_editor::json::cst::Document;
+use json_editor::json::lazy::{ChildrenPosition, Container, LazyDocument, LazyValue};
+use json_editor::json::comments::{Comment, CommentMap, Comments};
+use json_editor::json::error::{Diagnostic, Error, Severity};
+use json_editor::json::path::{Path, PathSegment};
+use json_editor::json::progress::Progress;
+use json_editor::json::span::{Span, Spans};
 use json_editor::json::value::JsonValueType;
+use json_editor::json::serializer::{self, SerializeOptions};
 
-#[derive(serde::Deserialize, serde::Serialize)]
+// Diagnostics grouped by the value they belong to
+type Problems<'a> = HashMap<&'a Path, Vec<&'a Diagnostic>>;
+
+#[derive(Default, serde::Deserialize, serde::Serialize)]
 #[serde(default)] // if we add new fields, give them default values when deserializing old state
 pub(crate) struct JsonEditor {
     current_file: Option<String>,
     #[serde(skip)]
-    current_data: Option<JsonValueType>
+    current_data: Option<JsonValueType>,
+    #[serde(skip)]
+    diagnostics: Vec<Diagnostic>,
+    #[serde(skip)]
+    source: String,
+    #[serde(skip)]
+    spans: Spans,
+    #[serde(skip)]
+    comments: CommentMap,
+    // The node picked in the tree, it is highlighted in the source
+    #[serde(skip)]
+    selected: Option<Path>,
+    // Line the source view still has to scroll to
+    #[serde(skip)]
+    jump_to_line: Option<usize>,
+    // Set instead of `current_data` for JSON Lines files
+    #[serde(skip)]
+    records: Option<Records>,
+    // Set instead of `current_data` for large files
+    #[serde(skip)]
+    lazy: Option<LazyTree>,
+    // The file that is being opened, the current one stays usable until it is ready
+    #[serde(skip)]
+    loading: Option<Loading>,
+}
+
+struct Loading {
+    path: PathBuf,
+    reading: Arc<Progress>,
+    parsing: Arc<Progress>,
+    result: mpsc::Receiver<Result<Loaded, String>>,
 }
 
-impl Default for JsonEditor {
-    fn default() -> Self {
-        Self {
-            current_file: None, // Restore path from stored path
-            current_data: None,
+impl Loading {
+    // Reads and parses the file on a worker thread
+    fn start(ctx: &egui::Context, path: PathBuf) -> Loading {
+        let (sender, result) = mpsc::channel();
+        let (reading, parsing) = (Arc::new(Progress::new()), Arc::new(Progress::new()));
+        let worker = (path.clone(), reading.clone(), parsing.clone(), ctx.clone());
+        std::thread::spawn(move || {
+            let (path, reading, parsing, ctx) = worker;
+            // Fails if the loading has been cancelled in the meantime
+            let _ = sender.send(load(&path, &reading, &parsing));
+            ctx.request_repaint();
+        });
+        Loading { path, reading, parsing, result }
+    }
+
+    fn cancel(&self) {
+        self.reading.cancel();
+        self.parsing.cancel();
+    }
+}
+
+enum Loaded {
+    Document(String, Recovered),
+    Records(Records),
+    Lazy(LazyTree),
+}
+
+// Bytes read from the file at a time while loading
+const READ_CHUNK_SIZE: usize = 1 << 20;
+
+// Files from this size on are only read where they are expanded in the tree
+const LAZY_FILE_SIZE: u64 = 64 << 20;
+
+// Children of a lazily read container that are read at a time
+const LAZY_CHILDREN_PER_PAGE: usize = 1000;
+
+struct LazyTree {
+    document: LazyDocument,
+    // Children read so far, by the offset of their container
+    children: HashMap<usize, LoadedChildren>,
+}
+
+#[derive(Default)]
+struct LoadedChildren {
+    items: Vec<(Option<String>, LazyValue)>,
+    error: Option<String>,
+    // Where the next page starts, once one has been read
+    next: Option<ChildrenPosition>,
+}
+
+impl LazyTree {
+    fn load_more(&self, container: &Container, loaded: &mut LoadedChildren) {
+        let mut children = match loaded.next {
+            Some(position) => self.document.children_from(container, position),
+            None => self.document.children(container),
+        };
+        for child in children.by_ref().take(LAZY_CHILDREN_PER_PAGE) {
+            match child {
+                Ok(child) => loaded.items.push(child),
+                Err(error) => loaded.error = Some(error.to_string()),
+            }
         }
+        loaded.next = Some(children.position());
+    }
+}
+
+// Records of a JSON Lines file shown on one page
+const RECORDS_PER_PAGE: usize = 50;
+
+// A JSON Lines file, edited one line at a time
+struct Records {
+    items: Vec<Record>,
+    page: usize,
+    // Hides the records that can not be read
+    skip_invalid: bool,
+    // The file as it was last loaded or saved, everything between the records is kept from it
+    source: String,
+}
+
+struct Record {
+    // Line in the file as it was loaded
+    number: usize,
+    // Where the line is in `Records::source`, without its line break
+    range: Range<usize>,
+    text: String,
+    value: Result<JsonValueType, Error>,
+}
+
+impl Record {
+    fn new(number: usize, range: Range<usize>, source: &str, value: Result<JsonValueType, Error>) -> Self {
+        Record { number, text: source[range.clone()].to_string(), range, value }
+    }
+
+    fn update(&mut self, options: &ParseOptions) {
+        self.value = to_object_with(&self.text, options);
     }
 }
 
@@ -34,12 +168,9 @@ impl JsonEditor {
         // Note that you must enable the `persistence` feature for this to work.
         if let Some(storage) = cc.storage {
             let mut app : JsonEditor = eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
-            if let Some(name) = &app.current_file {
-                let path = PathBuf::from(name);
-                app.current_data = load_json(&path);
-                if ! app.current_data.is_some() {
-                    app.current_file = None;
-                }
+            // Set again once the file has been loaded
+            if let Some(name) = app.current_file.take() {
+                app.loading = Some(Loading::start(&cc.egui_ctx, PathBuf::from(name)));
             }
             app
         } else {
@@ -48,6 +179,119 @@ impl JsonEditor {
 
     }
 
+    // `recovered` has to hold a value, see `load_json`
+    fn set_document(&mut self, source: String, recovered: Recovered) {
+        self.current_data = recovered.value;
+        self.diagnostics = recovered.diagnostics;
+        self.spans = recovered.spans;
+        self.comments = recovered.comments;
+        self.source = source;
+        self.selected = None;
+        self.jump_to_line = None;
+        self.records = None;
+        self.lazy = None;
+    }
+
+    fn set_records(&mut self, records: Records) {
+        *self = JsonEditor { current_file: self.current_file.take(), records: Some(records), ..Default::default() };
+    }
+
+    fn set_lazy(&mut self, lazy: LazyTree) {
+        *self = JsonEditor { current_file: self.current_file.take(), lazy: Some(lazy), ..Default::default() };
+    }
+
+    // Makes a file that has been loaded the current one
+    fn open(&mut self, path: PathBuf, loaded: Loaded) {
+        match loaded {
+            Loaded::Document(source, recovered) => self.set_document(source, recovered),
+            Loaded::Records(records) => self.set_records(records),
+            Loaded::Lazy(lazy) => self.set_lazy(lazy),
+        }
+        self.current_file = Some(path.display().to_string());
+    }
+
+    // Shows how far the loading has got, and opens the file once it is done
+    fn show_loading(&mut self, ctx: &egui::Context, modal: &Modal) {
+        let Some(loading) = &self.loading else {
+            return;
+        };
+        let result = match loading.result.try_recv() {
+            Ok(result) => result,
+            Err(mpsc::TryRecvError::Disconnected) => Err(format!("Loading {} failed", loading.path.display())),
+            Err(mpsc::TryRecvError::Empty) => {
+                let mut cancel = false;
+                egui::TopBottomPanel::bottom("loading_panel").show(ctx, |ui| {
+                    let (stage, progress) = if loading.parsing.total() > 0 { ("Parsing", &loading.parsing) } else { ("Reading", &loading.reading) };
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{} {}", stage, loading.path.display()));
+                        match progress.fraction() {
+                            Some(fraction) => {
+                                let text = format!("{} of {} bytes", progress.done(), progress.total());
+                                ui.add(egui::ProgressBar::new(fraction).text(text));
+                            }
+                            None => {ui.spinner();}
+                        }
+                        cancel = ui.button("Cancel").clicked();
+                    });
+                });
+                if cancel {
+                    loading.cancel();
+                    self.loading = None;
+                } else {
+                    // The progress is not pushed by the worker
+                    ctx.request_repaint_after(std::time::Duration::from_millis(100));
+                }
+                return;
+            }
+        };
+
+        let loading = self.loading.take().unwrap();
+        match result {
+            Ok(loaded) => self.open(loading.path, loaded),
+            Err(message) => {
+                modal.dialog()
+                    .with_title("Loading Failed")
+                    .with_body(message)
+                    .open();
+            }
+        }
+    }
+
+    // Selects a node and scrolls the source view to `line`
+    fn select(&mut self, path: Path, line: Option<usize>) {
+        self.jump_to_line = line.or_else(|| self.spans.get(&path).map(|x| x.value.line));
+        self.selected = Some(path);
+    }
+
+    // Writes the document back to its file, comments and formatting are kept
+    fn save_json(&mut self) -> Result<(), String> {
+        if let (Some(name), Some(records)) = (&self.current_file, &mut self.records) {
+            return save_lines(name, records);
+        }
+        let (Some(name), Some(value)) = (&self.current_file, &self.current_data) else {
+            return Ok(());
+        };
+        // The file is only rewritten where the value changed, unless it could not be read without
+        // repairs. Then the serializer writes it anew, with the comments that were found.
+        let options = ParseOptions::new().with_dialect(dialect_of(name.as_ref()));
+        let text = match Document::parse_with(&self.source, &options) {
+            Ok(mut document) => {
+                document.update(value).map_err(|e| format!("Could not write {}: {}", name, e))?;
+                document.to_string()
+            }
+            Err(_) => {
+                let text = serializer::to_string_with_comments(value, &self.comments, &SerializeOptions::new().with_line_width(100))
+                    .map_err(|e| format!("Could not write {}: {}", name, e))?;
+                text + "\n"
+            }
+        };
+        fs::write(name, &text).map_err(|e| format!("Could not write {}: {}", name, e))?;
+        // Positions in the source view have to match the file again
+        let recovered = to_object_recovering_with(&text, &options);
+        self.set_document(text, recovered);
+        Ok(())
+    }
+
     fn show_menu(&mut self, ui: &mut egui::Ui, modal: &Modal) {
         use egui::menu;
         menu::bar(ui, |ui| {
@@ -55,19 +299,24 @@ impl JsonEditor {
                 if ui.button("Quit").clicked() {
                     ui.ctx().send_viewport_cmd(egui::ViewportCommand::Close);
                 }
+                let can_save = self.current_file.is_some() && (self.current_data.is_some() || self.records.is_some());
+                if ui.add_enabled(can_save, egui::Button::new("Save")).clicked() {
+                    if let Err(message) = self.save_json() {
+                        modal.dialog()
+                            .with_title("Saving Failed")
+                            .with_body(message)
+                            .open();
+                    }
+                    ui.close_menu();
+                }
                 if ui.button("Open").clicked()  {
                     if let Some(path) = rfd::FileDialog::new().pick_file() {
-                        self.current_data = load_json(&path);
-                        if self.current_data.is_some() {
-                            self.current_file = Some(path.display().to_string());
-                        } else {
-                            // Check for parsing error vs file error
-                            modal.dialog()
-                                .with_title("Loading Failed")
-                                .with_body("Loading Failed")
-                                .open();
+                        if let Some(loading) = &self.loading {
+                            loading.cancel();
                         }
+                        self.loading = Some(Loading::start(ui.ctx(), path));
                     }
+                    ui.close_menu();
                 }
             });
         });
@@ -81,16 +330,53 @@ impl eframe::App for JsonEditor {
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             self.show_menu(ui, &modal);
         });
+        self.show_loading(ctx, &modal);
+
+        let mut clicked = None;
+        if !self.diagnostics.is_empty() {
+            egui::TopBottomPanel::bottom("problems_panel").resizable(true).show(ctx, |ui| {
+                if let Some(diagnostic) = show_problems(ui, &self.diagnostics) {
+                    clicked = Some((diagnostic.path.clone(), Some(diagnostic.error.line())));
+                }
+            });
+        }
 
         match self.current_data.as_ref() {
             None => {},
             Some(value) => {
+                let mut problems = Problems::new();
+                for diagnostic in &self.diagnostics {
+                    problems.entry(&diagnostic.path).or_default().push(diagnostic);
+                }
+                egui::SidePanel::right("source_panel").resizable(true).show(ctx, |ui| {
+                    let highlight = self.selected.as_ref().and_then(|x| self.spans.get(x)).map(|x| x.value);
+                    let problem_lines = self.diagnostics.iter()
+                        .flat_map(|x| std::iter::once((x.error.line(), x.severity)).chain(x.related.map(|r| (r.line, x.severity))))
+                        .collect();
+                    source_view(ui, &self.source, highlight, &problem_lines, self.jump_to_line.take());
+                });
+                let mut view = TreeView { problems, comments: &self.comments, selected: self.selected.as_ref(), clicked: None };
                 egui::CentralPanel::default().show(ctx, |ui| {
-                    simple_json_view(ui, value);
+                    if let Some(path) = simple_json_view(ui, value, &mut view) {
+                        clicked = Some((path, None));
+                    }
                 });
             }
         }
 
+        if let Some(records) = self.records.as_mut() {
+            let options = ParseOptions::new().with_dialect(self.current_file.as_deref().map_or(Dialect::Json, |x| dialect_of(x.as_ref())));
+            egui::CentralPanel::default().show(ctx, |ui| records_view(ui, records, &options));
+        }
+
+        if let Some(lazy) = self.lazy.as_mut() {
+            egui::CentralPanel::default().show(ctx, |ui| lazy_view(ui, lazy));
+        }
+
+        if let Some((path, line)) = clicked {
+            self.select(path, line);
+        }
+
         modal.show_dialog();
     }
 
@@ -99,67 +385,390 @@ impl eframe::App for JsonEditor {
     }
 }
 
-fn load_json(path: &PathBuf) -> Option<JsonValueType> {
-    let result = fs::read_to_string(path).expect("Could not Open File");
-    match to_object(result.as_ref()) {
-        Err(_) => None,
-        Ok(result) => Some(result)
+// Runs on the worker thread of `Loading`
+fn load(path: &std::path::Path, reading: &Progress, parsing: &Progress) -> Result<Loaded, String> {
+    if is_json_lines(path) {
+        Ok(Loaded::Records(load_lines(path, reading, parsing)?))
+    } else if fs::metadata(path).is_ok_and(|x| x.len() >= LAZY_FILE_SIZE) {
+        Ok(Loaded::Lazy(load_lazy(path, parsing)?))
+    } else {
+        let (source, recovered) = load_json(path, reading, parsing)?;
+        Ok(Loaded::Document(source, recovered))
     }
 }
 
-fn simple_json_view(ui: &mut egui::Ui, value: &JsonValueType) {
+fn read_file(path: &std::path::Path, progress: &Progress) -> Result<String, String> {
+    let error = |e: &dyn std::fmt::Display| format!("Could not open {}: {}", path.display(), e);
+    let mut file = fs::File::open(path).map_err(|e| error(&e))?;
+    progress.set_total(file.metadata().map_or(0, |x| x.len() as usize));
+    let mut content = Vec::with_capacity(progress.total());
+    let mut chunk = vec![0; READ_CHUNK_SIZE];
+    loop {
+        if progress.is_cancelled() {
+            return Err("Loading was cancelled".to_string());
+        }
+        let read = file.read(&mut chunk).map_err(|e| error(&e))?;
+        if read == 0 {
+            break;
+        }
+        content.extend_from_slice(&chunk[..read]);
+        progress.set_done(content.len());
+    }
+    String::from_utf8(content).map_err(|e| error(&e))
+}
+
+// Broken files are loaded as far as possible, the problems found are returned alongside
+fn load_json(path: &std::path::Path, reading: &Progress, parsing: &Progress) -> Result<(String, Recovered), String> {
+    let result = read_file(path, reading)?;
+    let options = ParseOptions::new().with_dialect(dialect_of(path));
+    let recovered = to_object_recovering_with_progress(result.as_ref(), &options, parsing).map_err(|e| e.to_string())?;
+    if recovered.value.is_none() {
+        return Err(recovered.diagnostics.iter().map(|x| x.error.to_string()).collect::<Vec<_>>().join("\n"));
+    }
+    Ok((result, recovered))
+}
+
+// Only the structure is read up front, values are read as they are expanded
+fn load_lazy(path: &std::path::Path, parsing: &Progress) -> Result<LazyTree, String> {
+    let options = ParseOptions::new().with_dialect(dialect_of(path));
+    // Safety: like other viewers of large files the editor maps the file instead of reading it,
+    // and relies on it not being changed while it is shown
+    let document = unsafe { LazyDocument::open_with_progress(path, &options, parsing) };
+    if parsing.is_cancelled() {
+        return Err("Loading was cancelled".to_string());
+    }
+    let document = document.map_err(|e| format!("Could not open {}: {}", path.display(), e))?;
+    Ok(LazyTree { document, children: HashMap::new() })
+}
+
+// Every line is read on its own, a line that can not be read is kept as text to fix it
+fn load_lines(path: &std::path::Path, reading: &Progress, parsing: &Progress) -> Result<Records, String> {
+    let content = read_file(path, reading)?;
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    for line in content.split_inclusive('\n') {
+        let text = line.strip_suffix('\n').unwrap_or(line);
+        ranges.push(start..start + text.strip_suffix('\r').unwrap_or(text).len());
+        start += line.len();
+    }
+    let options = ParseOptions::new().with_dialect(dialect_of(path));
+    parsing.set_total(content.len());
+    let mut items = Vec::new();
+    for line in lines::Lines::new(&content).with_options(&options) {
+        if parsing.is_cancelled() {
+            return Err("Loading was cancelled".to_string());
+        }
+        items.push(match line {
+            Ok(line) => {
+                parsing.set_done(line.offset);
+                Record::new(line.number, ranges[line.number - 1].clone(), &content, Ok(line.value))
+            }
+            Err(error) => Record::new(error.line(), ranges[error.line() - 1].clone(), &content, Err(error)),
+        });
+    }
+    Ok(Records { items, page: 0, skip_invalid: false, source: content })
+}
+
+// Only the text of the records is replaced, blank lines and line breaks stay as they were
+fn save_lines(name: &str, records: &mut Records) -> Result<(), String> {
+    let mut text = String::with_capacity(records.source.len());
+    let mut copied = 0;
+    let mut ranges = Vec::with_capacity(records.items.len());
+    for record in &records.items {
+        text.push_str(&records.source[copied..record.range.start]);
+        ranges.push(text.len()..text.len() + record.text.len());
+        text.push_str(&record.text);
+        copied = record.range.end;
+    }
+    text.push_str(&records.source[copied..]);
+    fs::write(name, &text).map_err(|e| format!("Could not write {}: {}", name, e))?;
+
+    for (record, range) in records.items.iter_mut().zip(ranges) {
+        record.range = range;
+    }
+    records.source = text;
+    Ok(())
+}
+
+fn is_json_lines(path: &std::path::Path) -> bool {
+    matches!(path.extension().and_then(|x| x.to_str()), Some(extension)
+        if ["jsonl", "ndjson"].iter().any(|x| extension.eq_ignore_ascii_case(x)))
+}
+
+// Files that VS Code and TypeScript read as JSON with comments
+const JSONC_FILE_NAMES: [&str; 9] = ["tsconfig.json", "jsconfig.json", "settings.json", "launch.json", "tasks.json",
+    "keybindings.json", "extensions.json", "devcontainer.json", ".devcontainer.json"];
+
+fn dialect_of(path: &std::path::Path) -> Dialect {
+    let name = path.file_name().and_then(|x| x.to_str()).unwrap_or_default();
+    match path.extension().and_then(|x| x.to_str()) {
+        Some(extension) if extension.eq_ignore_ascii_case("json5") => Dialect::Json5,
+        Some(extension) if extension.eq_ignore_ascii_case("jsonc") => Dialect::Jsonc,
+        _ if JSONC_FILE_NAMES.contains(&name) || name.starts_with("tsconfig.") => Dialect::Jsonc,
+        _ => Dialect::Json,
+    }
+}
+
+// Returns the problem that was clicked on
+fn show_problems<'a>(ui: &mut egui::Ui, diagnostics: &'a [Diagnostic]) -> Option<&'a Diagnostic> {
+    let mut clicked = None;
+    let errors = diagnostics.iter().filter(|x| x.severity == Severity::Error).count();
+    if errors > 0 {
+        ui.label(format!("{} problem(s) found, the document has been repaired where possible", errors));
+    }
+    if errors < diagnostics.len() {
+        ui.label(format!("{} warning(s)", diagnostics.len() - errors));
+    }
+    egui::ScrollArea::vertical().auto_shrink([false, true]).show(ui, |ui| {
+        for diagnostic in diagnostics {
+            let error = &diagnostic.error;
+            let mut text = format!("{}:{}  {}  at {}", error.line(), error.column(), error.kind(), diagnostic.path);
+            if let Some(related) = diagnostic.related {
+                text += &format!(", first at {}:{}", related.line, related.column);
+            }
+            let text = egui::RichText::new(text).color(severity_color(ui, diagnostic.severity));
+            if ui.add(Label::new(text).sense(Sense::click()))
+                .on_hover_text(egui::RichText::new(error.snippet()).monospace())
+                .clicked() {
+                clicked = Some(diagnostic);
+            }
+        }
+    });
+    clicked
+}
+
+fn severity_color(ui: &egui::Ui, severity: Severity) -> Color32 {
+    match severity {
+        Severity::Error => ui.visuals().error_fg_color,
+        Severity::Warning => ui.visuals().warn_fg_color,
+    }
+}
+
+// Shows the source with line numbers, `highlight` is the selected node
+fn source_view(ui: &mut egui::Ui, source: &str, highlight: Option<Span>, problem_lines: &HashMap<usize, Severity>, jump_to_line: Option<usize>) {
+    let lines: Vec<&str> = source.lines().collect();
+    let highlighted_lines = highlight.map_or(0..=0, |x| {
+        let end = x.end.clamp(x.start, source.len());
+        x.line..=x.line + source[x.start.min(end)..end].matches('\n').count()
+    });
+    let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+    let mut scroll = egui::ScrollArea::both().auto_shrink([false, false]);
+    if let Some(line) = jump_to_line {
+        scroll = scroll.vertical_scroll_offset((line.saturating_sub(3) as f32) * (row_height + ui.spacing().item_spacing.y));
+    }
+    scroll.show_rows(ui, row_height, lines.len(), |ui, rows| {
+        for row in rows {
+            let number = row + 1;
+            let mut text = egui::RichText::new(format!("{:>5}  {}", number, lines[row])).monospace();
+            if let Some(severity) = problem_lines.get(&number) {
+                text = text.color(severity_color(ui, *severity));
+            }
+            if highlight.is_some() && highlighted_lines.contains(&number) {
+                text = text.background_color(ui.visuals().selection.bg_fill);
+            }
+            ui.add(Label::new(text).extend());
+        }
+    });
+}
+
+// A page of records, each can be edited as text and is read again on every change
+fn records_view(ui: &mut egui::Ui, records: &mut Records, options: &ParseOptions) {
+    let invalid = records.items.iter().filter(|x| x.value.is_err()).count();
+    let shown: Vec<usize> = (0..records.items.len())
+        .filter(|x| !records.skip_invalid || records.items[*x].value.is_ok())
+        .collect();
+    let pages = shown.len().div_ceil(RECORDS_PER_PAGE).max(1);
+    records.page = records.page.min(pages - 1);
+
+    ui.horizontal(|ui| {
+        if ui.add_enabled(records.page > 0, egui::Button::new("◀")).clicked() {
+            records.page -= 1;
+        }
+        ui.label(format!("Page {} of {}", records.page + 1, pages));
+        if ui.add_enabled(records.page + 1 < pages, egui::Button::new("▶")).clicked() {
+            records.page += 1;
+        }
+        ui.separator();
+        ui.label(format!("{} record(s), {} invalid", records.items.len(), invalid));
+        ui.checkbox(&mut records.skip_invalid, "Skip invalid lines");
+    });
+    ui.separator();
+
+    let comments = CommentMap::default();
+    let mut view = TreeView { problems: Problems::new(), comments: &comments, selected: None, clicked: None };
+    egui::ScrollArea::both().auto_shrink([false, false]).show(ui, |ui| {
+        for &index in shown.iter().skip(records.page * RECORDS_PER_PAGE).take(RECORDS_PER_PAGE) {
+            let record = &mut records.items[index];
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(format!("{:>5}", record.number)).monospace());
+                let edit = egui::TextEdit::singleline(&mut record.text).code_editor().desired_width(f32::INFINITY);
+                if ui.add(edit).changed() {
+                    record.update(options);
+                }
+            });
+            match &record.value {
+                Ok(value) => {
+                    egui::CollapsingHeader::new("value").id_source(("record", record.number)).show(ui, |ui| {
+                        view.draw_json_value(ui, value, &Path::root());
+                    });
+                }
+                Err(error) => {
+                    let text = egui::RichText::new(format!("{}:{}  {}", record.number, error.column(), error.kind()))
+                        .color(severity_color(ui, Severity::Error));
+                    ui.label(text).on_hover_text(egui::RichText::new(error.snippet()).monospace());
+                }
+            }
+        }
+    });
+}
+
+fn lazy_view(ui: &mut egui::Ui, tree: &mut LazyTree) {
+    ui.label(format!("{} bytes, {} arrays and objects, values are read when they are expanded",
+                     tree.document.len(), tree.document.containers()));
+    ui.separator();
+    egui::ScrollArea::both().auto_shrink([false, false]).show(ui, |ui| {
+        match tree.document.root() {
+            Ok(value) => draw_lazy_value(ui, tree, String::new(), &value),
+            Err(error) => {ui.colored_label(severity_color(ui, Severity::Error), error.to_string());}
+        }
+    });
+}
+
+fn draw_lazy_value(ui: &mut egui::Ui, tree: &mut LazyTree, label: String, value: &LazyValue) {
+    match value {
+        LazyValue::Scalar(JsonValueType::JsonTypeString(val)) => {ui.label(label + val);}
+        LazyValue::Scalar(val) => {ui.label(label + &val.to_string());}
+        LazyValue::Container(container) => {
+            let brackets = if container.is_object { "{…}" } else { "[…]" };
+            egui::CollapsingHeader::new(format!("{}{} {} item(s)", label, brackets, container.len))
+                .id_source(("lazy", container.start))
+                .show(ui, |ui| draw_lazy_children(ui, tree, container));
+        }
+    }
+}
+
+// Reads the first page of children when the container is expanded for the first time
+fn draw_lazy_children(ui: &mut egui::Ui, tree: &mut LazyTree, container: &Container) {
+    let mut loaded = tree.children.remove(&container.start).unwrap_or_default();
+    if loaded.items.is_empty() && loaded.error.is_none() {
+        tree.load_more(container, &mut loaded);
+    }
+    for (i, (key, value)) in loaded.items.iter().enumerate() {
+        let label = key.as_ref().map_or(i.to_string() + " : ", |x| x.clone() + " : ");
+        draw_lazy_value(ui, tree, label, value);
+    }
+    if let Some(error) = &loaded.error {
+        ui.colored_label(severity_color(ui, Severity::Error), error);
+    } else if loaded.items.len() < container.len
+        && ui.button(format!("Show more ({} of {})", loaded.items.len(), container.len)).clicked() {
+        tree.load_more(container, &mut loaded);
+    }
+    tree.children.insert(container.start, loaded);
+}
+
+// Returns the path of the node that was clicked on
+fn simple_json_view(ui: &mut egui::Ui, value: &JsonValueType, view: &mut TreeView) -> Option<Path> {
     egui::ScrollArea::both().scroll_bar_visibility(VisibleWhenNeeded)
         .auto_shrink([false, false])
         .show(ui, |ui| {
-            ui.with_layout(egui::Layout::top_down(egui::Align::LEFT).with_cross_justify(true),
-                           |ui| draw_json_value(ui, value));
+            ui.with_layout(egui::Layout::top_down(egui::Align::LEFT).with_cross_justify(true), |ui| {
+                let root = Path::root();
+                view.draw_comments(ui, &root, |x| &x.leading);
+                ui.horizontal(|ui| {
+                    view.draw_json_value(ui, value, &root);
+                    view.draw_comments(ui, &root, |x| &x.trailing);
+                });
+            });
         },
         );
+    view.clicked.take()
+}
+
+// What the tree shows next to the values, and the node that was clicked on
+struct TreeView<'a> {
+    problems: Problems<'a>,
+    comments: &'a CommentMap,
+    selected: Option<&'a Path>,
+    clicked: Option<Path>,
 }
 
+impl TreeView<'_> {
+    // The label in front of a member or element, clicking it selects the node
+    fn draw_node_label(&mut self, ui: &mut egui::Ui, text: String, path: Path) {
+        if ui.selectable_label(self.selected == Some(&path), text).clicked() {
+            self.clicked = Some(path);
+        }
+    }
 
+    // Marks a value that had to be repaired while loading
+    fn draw_problem_marker(&self, ui: &mut egui::Ui, path: &Path) {
+        if let Some(diagnostics) = self.problems.get(path) {
+            let text: Vec<String> = diagnostics.iter()
+                .map(|x| format!("{}:{} {}", x.error.line(), x.error.column(), x.error.kind()))
+                .collect();
+            let severity = if diagnostics.iter().any(|x| x.severity == Severity::Error) { Severity::Error } else { Severity::Warning };
+            ui.colored_label(severity_color(ui, severity), "⚠").on_hover_text(text.join("\n"));
+        }
+    }
 
-fn draw_json_value(ui: &mut egui::Ui, value: &JsonValueType) {
-    match value {
-        JsonValueType::JsonTypeNull => {ui.label("null");}
-        JsonValueType::JsonTypeBool(val) => {
-            let text : String;
-            if *val {
-                text = "true".to_owned();
-            } else {
-                text = "false".to_owned();
-            }
-            if ui.add(Label::new(text).sense(Sense::click())).clicked()
-            {
-                show_edit_panel(ui, value);
-            }
-        }
-        JsonValueType::JsonTypeNumber(val) => {ui.label(val.to_string());}
-        JsonValueType::JsonTypeObject(obj) => {
-            egui::CollapsingHeader::new("").id_source(obj).show(ui, |ui| {
-                ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
-                    for item in obj.0.iter() {
-                        ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
-                            ui.label(item.0);
-                            draw_json_value(ui, &item.1);
-                        });
-                    }
-                });
-            });
+    fn draw_comments(&self, ui: &mut egui::Ui, path: &Path, which: impl Fn(&Comments) -> &Vec<Comment>) {
+        if let Some(comments) = self.comments.get(path) {
+            for comment in which(comments) {
+                ui.label(egui::RichText::new(&comment.text).italics().weak());
+            }
         }
-        JsonValueType::JsonTypeArray(Array(val)) => {
-            ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
-                for (i, item) in val.iter().enumerate()
+    }
+
+    // One row per member or element, with the comments of the child around it
+    fn draw_child(&mut self, ui: &mut egui::Ui, label: String, value: &JsonValueType, path: Path) {
+        self.draw_comments(ui, &path, |x| &x.leading);
+        ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+            self.draw_node_label(ui, label, path.clone());
+            self.draw_json_value(ui, value, &path);
+            self.draw_comments(ui, &path, |x| &x.trailing);
+        });
+    }
+
+    fn draw_json_value(&mut self, ui: &mut egui::Ui, value: &JsonValueType, path: &Path) {
+        self.draw_problem_marker(ui, path);
+        match value {
+            JsonValueType::JsonTypeNull => {ui.label("null");}
+            JsonValueType::JsonTypeBool(val) => {
+                let text = if *val {
+                    "true".to_owned()
+                } else {
+                    "false".to_owned()
+                };
+                if ui.add(Label::new(text).sense(Sense::click())).clicked()
                 {
-                    ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
-                        ui.label(i.to_string() + " : ");
-                        draw_json_value(ui, item);
+                    show_edit_panel(ui, value);
+                }
+            }
+            JsonValueType::JsonTypeNumber(val) => {ui.label(val.to_string());}
+            JsonValueType::JsonTypeObject(obj) => {
+                // Keyed by path so that the expansion state stays with the node when the tree is edited
+                // or loaded again
+                egui::CollapsingHeader::new("").id_source(("node", path)).show(ui, |ui| {
+                    ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
+                        for item in obj.0.iter() {
+                            self.draw_child(ui, item.0.clone(), item.1, path.join(PathSegment::Key(item.0.clone())));
+                        }
+                        self.draw_comments(ui, path, |x| &x.inner);
                     });
+                });
+            }
+            JsonValueType::JsonTypeArray(Array(val)) => {
+                ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
+                    for (i, item) in val.iter().enumerate()
+                    {
+                        self.draw_child(ui, i.to_string() + " : ", item, path.join(PathSegment::Index(i)));
+                    }
+                    self.draw_comments(ui, path, |x| &x.inner);
                 }
+                );
             }
-            );
+            JsonValueType::JsonTypeString(val) => {ui.label(val);}
         }
-        JsonValueType::JsonTypeString(val) => {ui.label(val);}
     }
 }
 
@@ -167,6 +776,6 @@ fn show_edit_panel(ui: &mut Ui, value: &JsonValueType) {
     egui::SidePanel::right("Properties").show(ui.ctx(), |ui| draw_edit_panel(ui, value));
 }
 
-fn draw_edit_panel(ui: &mut Ui, value: &JsonValueType)  {
+fn draw_edit_panel(_ui: &mut Ui, _value: &JsonValueType)  {
 
 }
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use super::path::Path;
+use super::span::Spans;
+
+/// A comment, kept exactly as it was written including the `//` or `/* */`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Comment {
+    pub text: String,
+}
+
+impl Comment {
+    pub fn new(text: impl Into<String>) -> Self {
+        Comment { text: text.into() }
+    }
+
+    /// Whether this is a `//` comment, which runs to the end of the line.
+    pub fn is_line(&self) -> bool {
+        self.text.starts_with("//")
+    }
+
+    /// The text without the comment markers and surrounding whitespace.
+    pub fn body(&self) -> &str {
+        let text = match self.text.strip_prefix("//") {
+            Some(text) => text,
+            None => self.text.strip_prefix("/*").and_then(|x| x.strip_suffix("*/")).unwrap_or(&self.text),
+        };
+        text.trim()
+    }
+}
+
+/// The comments attached to one value.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Comments {
+    /// Comments on the lines before the value, or before the key of an object member.
+    pub leading: Vec<Comment>,
+
+    /// Comments after the value on the same line.
+    pub trailing: Vec<Comment>,
+
+    /// Comments inside an array or object after its last child, or inside an empty one.
+    pub inner: Vec<Comment>,
+}
+
+impl Comments {
+    pub fn is_empty(&self) -> bool {
+        self.leading.is_empty() && self.trailing.is_empty() && self.inner.is_empty()
+    }
+}
+
+/// Comments of a document, keyed by the path of the value they belong to.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct CommentMap(pub HashMap<Path, Comments>);
+
+impl CommentMap {
+    pub fn get(&self, path: &Path) -> Option<&Comments> {
+        self.0.get(path)
+    }
+
+    pub fn get_mut(&mut self, path: &Path) -> &mut Comments {
+        self.0.entry(path.clone()).or_default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.values().map(|x| x.leading.len() + x.trailing.len() + x.inner.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.values().all(Comments::is_empty)
+    }
+
+    /// Attaches the comments at the byte ranges `found` in `source` to the values in `spans`.
+    ///
+    /// A comment after a value on the same line trails that value. Any other comment leads the
+    /// next value in the same container, or is inside the container when nothing follows.
+    pub(crate) fn attach(source: &[u8], found: &[(usize, usize)], spans: &Spans) -> CommentMap {
+        let mut result = CommentMap::default();
+        if found.is_empty() {
+            return result;
+        }
+        // Sorted once so that each comment only needs binary searches, shallower values first
+        // where they end or start at the same place
+        let mut ends: Vec<_> = spans.iter().map(|(path, span)| (span.value.end, path.0.len(), path)).collect();
+        ends.sort_unstable_by_key(|x| (x.0, x.1));
+        let mut starts: Vec<_> = spans.iter().map(|(path, span)| (span.value.start, path)).collect();
+        starts.sort_unstable_by_key(|x| x.0);
+        let mut firsts: Vec<_> = spans.iter()
+            .map(|(path, span)| (span.key.map_or(span.value.start, |x| x.start), path.0.len(), path))
+            .collect();
+        firsts.sort_unstable_by_key(|x| (x.0, x.1));
+
+        for &(start, end) in found {
+            let comment = Comment::new(String::from_utf8_lossy(&source[start..end]));
+
+            // Only commas and blanks may be between a value and its trailing comment
+            let before = source[..start].iter().rposition(|x| !matches!(x, b' ' | b'\t' | b',')).map_or(0, |x| x + 1);
+            let previous = ends.get(ends.partition_point(|x| x.0 < before)).filter(|x| x.0 == before && before > 0);
+            if let Some((_, _, path)) = previous {
+                result.get_mut(path).trailing.push(comment);
+                continue;
+            }
+
+            // The innermost container is the value that starts last before the comment, or one
+            // of its ancestors
+            let container = starts[..starts.partition_point(|x| x.0 < start)].last().and_then(|(_, path)| {
+                (0..=path.0.len()).rev()
+                    .map(|depth| Path(path.0[..depth].to_vec()))
+                    .find(|x| spans.get(x).is_some_and(|span| end < span.value.end))
+            });
+            let limit = container.as_ref().and_then(|x| spans.get(x)).map_or(usize::MAX, |span| span.value.end);
+            // Anything that starts inside the container is below it
+            let next = firsts.get(firsts.partition_point(|x| x.0 < end)).filter(|x| x.0 < limit);
+            match (next, container) {
+                (Some((_, _, path)), _) => result.get_mut(path).leading.push(comment),
+                (None, Some(path)) => result.get_mut(&path).inner.push(comment),
+                // After the top-level value
+                (None, None) => result.get_mut(&Path::root()).trailing.push(comment),
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_comment_body() {
+        assert_eq!(Comment::new("// line ").body(), "line");
+        assert!(Comment::new("// line").is_line());
+        assert_eq!(Comment::new("/* block */").body(), "block");
+        assert!(!Comment::new("/* block */").is_line());
+    }
+}
@@ -0,0 +1,502 @@
+use std::collections::HashSet;
+use std::fmt;
+use super::error::Error;
+use super::options::{Dialect, ParseOptions};
+use super::path::{Path, PathSegment};
+use super::value::JsonValueType;
+use super::serializer::write_string;
+use super::{to_object_with, Array, Object};
+
+/// A document that keeps every byte of its source, so that it can be edited by path and written
+/// back with everything that was not edited exactly as it was.
+///
+/// Whitespace, comments, key order, repeated keys and the spelling of numbers and strings are all
+/// kept. Values that are put in by an edit are written on one line.
+///
+/// ```
+/// use json_editor::json::cst::Document;
+/// use json_editor::json::number::Number;
+/// use json_editor::json::path::{Path, PathSegment};
+/// use json_editor::json::value::JsonValueType;
+///
+/// let mut document = Document::parse("{\n  \"b\" : 1.50,\n  \"a\" : 2\n}\n").unwrap();
+/// let path = Path::root().join(PathSegment::Key("a".to_string()));
+/// document.set(&path, &JsonValueType::JsonTypeNumber(Number::from(3))).unwrap();
+/// assert_eq!(document.to_string(), "{\n  \"b\" : 1.50,\n  \"a\" : 3\n}\n");
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct Document {
+    before: String,
+    node: Node,
+    after: String,
+}
+
+/// A value in a [`Document`].
+#[derive(Debug, PartialEq, Clone)]
+pub enum Node {
+    /// The source text of a string, number or literal.
+    Scalar(String),
+    Array(Container),
+    Object(Container),
+}
+
+/// The children of an array or object with the text between them.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Container {
+    is_object: bool,
+    items: Vec<Item>,
+    trailing_comma: bool,
+    // Everything between the last comma and the closing bracket, or inside an empty container
+    inner: String,
+}
+
+// An array element or object member. Whitespace and comments between the previous comma or the
+// opening bracket and the item are in `before`, those up to the next comma in `after`.
+#[derive(Debug, PartialEq, Clone)]
+struct Item {
+    before: String,
+    key: Option<Key>,
+    node: Node,
+    after: String,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+struct Key {
+    // As written, with its quotes
+    text: String,
+    name: String,
+    before_colon: String,
+    after_colon: String,
+}
+
+/// Why an edit of a [`Document`] could not be made.
+#[derive(Debug, PartialEq, Clone)]
+pub enum EditError {
+    /// The path leads through a value that does not exist or is not a container of the right
+    /// kind, or to an index past the end of an array.
+    NotFound(Path),
+
+    /// The top-level value can only be replaced, not removed.
+    RemoveRoot,
+
+    /// The new value for the path holds NaN or an infinity, which JSON has no notation for.
+    NonFiniteNumber(Path),
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EditError::NotFound(path) => write!(f, "no value at {}", path),
+            EditError::RemoveRoot => f.write_str("the top-level value can not be removed"),
+            EditError::NonFiniteNumber(path) => write!(f, "the value for {} holds a number that JSON can not represent", path),
+        }
+    }
+}
+
+impl std::error::Error for EditError {}
+
+impl Document {
+    /// Parses strict JSON, see [`Document::parse_with`].
+    pub fn parse(source: &str) -> Result<Document, Error> {
+        Self::parse_with(source, &ParseOptions::default())
+    }
+
+    /// Parses `source` in the dialect of `options`. The source has to be valid, errors are
+    /// reported as by [`super::to_object_with`].
+    pub fn parse_with(source: &str, options: &ParseOptions) -> Result<Document, Error> {
+        to_object_with(source, options)?;
+        let mut builder = Builder { source, position: 0 };
+        let before = builder.trivia();
+        let node = builder.node();
+        let after = builder.trivia();
+        Ok(Document { before, node, after })
+    }
+
+    /// The top-level value.
+    pub fn root(&self) -> &Node {
+        &self.node
+    }
+
+    /// The value at `path`, for repeated keys the last member like in the parsed value.
+    pub fn get(&self, path: &Path) -> Option<&Node> {
+        let mut node = &self.node;
+        for segment in &path.0 {
+            node = &node.container()?.find(segment)?.node;
+        }
+        Some(node)
+    }
+
+    /// Changes the value at `path` to `value`, or adds it when the path names a missing key of
+    /// an object or the index just past the end of an array.
+    ///
+    /// Only what differs from the current value is written, children that stay the same keep
+    /// their text. Of a repeated key only the last member is changed.
+    pub fn set(&mut self, path: &Path, value: &JsonValueType) -> Result<(), EditError> {
+        check_numbers(path, value)?;
+        let Some((last, parent)) = path.0.split_last() else {
+            self.node.update(value);
+            return Ok(());
+        };
+        let container = self.container_mut(parent).ok_or_else(|| EditError::NotFound(path.clone()))?;
+        if let Some(item) = container.find_mut(last) {
+            item.node.update(value);
+            return Ok(());
+        }
+        match last {
+            PathSegment::Key(key) if container.is_object => container.push(Some(key), Node::new(value)),
+            PathSegment::Index(index) if !container.is_object && *index == container.items.len() => {
+                container.push(None, Node::new(value))
+            }
+            _ => return Err(EditError::NotFound(path.clone())),
+        }
+        Ok(())
+    }
+
+    /// Removes the value at `path` from its array or object, every member is removed for a
+    /// repeated key.
+    pub fn remove(&mut self, path: &Path) -> Result<(), EditError> {
+        let (last, parent) = path.0.split_last().ok_or(EditError::RemoveRoot)?;
+        let container = self.container_mut(parent).ok_or_else(|| EditError::NotFound(path.clone()))?;
+        let indices = container.positions(last);
+        if indices.is_empty() {
+            return Err(EditError::NotFound(path.clone()));
+        }
+        for index in indices.into_iter().rev() {
+            container.remove(index);
+        }
+        Ok(())
+    }
+
+    /// Changes the whole document to `value`, like [`Document::set`] on the top-level value.
+    pub fn update(&mut self, value: &JsonValueType) -> Result<(), EditError> {
+        self.set(&Path::root(), value)
+    }
+
+    fn container_mut(&mut self, path: &[PathSegment]) -> Option<&mut Container> {
+        let mut node = &mut self.node;
+        for segment in path {
+            node = &mut node.container_mut()?.find_mut(segment)?.node;
+        }
+        node.container_mut()
+    }
+}
+
+// Checks that every number in `value` can be written, so that an edit is made in full or not at all
+fn check_numbers(path: &Path, value: &JsonValueType) -> Result<(), EditError> {
+    let mut stack = vec![value];
+    while let Some(value) = stack.pop() {
+        match value {
+            JsonValueType::JsonTypeNumber(val) if val.to_json().is_none() => {
+                return Err(EditError::NonFiniteNumber(path.clone()));
+            }
+            JsonValueType::JsonTypeArray(Array(values)) => stack.extend(values),
+            JsonValueType::JsonTypeObject(Object(members)) => stack.extend(members.values()),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+impl Node {
+    fn new(value: &JsonValueType) -> Node {
+        let mut text = String::new();
+        match value {
+            JsonValueType::JsonTypeNull => text.push_str("null"),
+            JsonValueType::JsonTypeBool(val) => text.push_str(if *val { "true" } else { "false" }),
+            // Non-finite numbers are turned away by `check_numbers` before any edit
+            JsonValueType::JsonTypeNumber(val) => text = val.to_json().unwrap_or_else(|| "null".to_string()),
+            JsonValueType::JsonTypeString(val) => write_string(&mut text, val, false),
+            JsonValueType::JsonTypeArray(Array(values)) => {
+                let mut container = Container::default();
+                for value in values {
+                    container.push(None, Node::new(value));
+                }
+                return Node::Array(container);
+            }
+            JsonValueType::JsonTypeObject(Object(members)) => {
+                let mut container = Container { is_object: true, ..Container::default() };
+                for (key, value) in members {
+                    container.push(Some(key), Node::new(value));
+                }
+                return Node::Object(container);
+            }
+        }
+        Node::Scalar(text)
+    }
+
+    /// The value of this node, with repeated keys handled as by the default options.
+    pub fn to_value(&self) -> JsonValueType {
+        to_object_with(&self.to_string(), &ParseOptions::new().with_dialect(Dialect::Json5)).unwrap_or(JsonValueType::JsonTypeNull)
+    }
+
+    fn container(&self) -> Option<&Container> {
+        match self {
+            Node::Array(container) | Node::Object(container) => Some(container),
+            Node::Scalar(_) => None,
+        }
+    }
+
+    fn container_mut(&mut self) -> Option<&mut Container> {
+        match self {
+            Node::Array(container) | Node::Object(container) => Some(container),
+            Node::Scalar(_) => None,
+        }
+    }
+
+    // Keeps the text of everything that already has the new value
+    fn update(&mut self, value: &JsonValueType) {
+        if matches!(self, Node::Scalar(_)) && self.to_value() == *value {
+            return;
+        }
+        match (&mut *self, value) {
+            (Node::Array(container), JsonValueType::JsonTypeArray(Array(values))) => {
+                while container.items.len() > values.len() {
+                    container.remove(container.items.len() - 1);
+                }
+                for (i, value) in values.iter().enumerate() {
+                    match container.items.get_mut(i) {
+                        Some(item) => item.node.update(value),
+                        None => container.push(None, Node::new(value)),
+                    }
+                }
+            }
+            (Node::Object(container), JsonValueType::JsonTypeObject(Object(members))) => {
+                // Earlier members with a repeated key do not show in the value and are left alone.
+                // Afterwards `seen` holds every key that is still there.
+                let mut seen = HashSet::new();
+                for i in (0..container.items.len()).rev() {
+                    let name = &container.items[i].key.as_ref().unwrap().name;
+                    match members.get(name) {
+                        Some(_) if !seen.insert(name.clone()) => {}
+                        Some(value) => container.items[i].node.update(value),
+                        None => container.remove(i),
+                    }
+                }
+                let added: Vec<(&String, &JsonValueType)> = members.iter()
+                    .filter(|(key, _)| !seen.contains(*key))
+                    .collect();
+                for (key, value) in added {
+                    container.push(Some(key), Node::new(value));
+                }
+            }
+            _ => *self = Node::new(value),
+        }
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Node::Scalar(text) => f.write_str(text),
+            Node::Array(container) => container.write(f, ('[', ']')),
+            Node::Object(container) => container.write(f, ('{', '}')),
+        }
+    }
+}
+
+impl Container {
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    fn find(&self, segment: &PathSegment) -> Option<&Item> {
+        self.positions(segment).last().map(|x| &self.items[*x])
+    }
+
+    fn find_mut(&mut self, segment: &PathSegment) -> Option<&mut Item> {
+        self.positions(segment).last().map(|x| &mut self.items[*x])
+    }
+
+    // Indices of the items that `segment` names, more than one for a repeated key
+    fn positions(&self, segment: &PathSegment) -> Vec<usize> {
+        match segment {
+            PathSegment::Index(index) if !self.is_object && *index < self.items.len() => vec![*index],
+            PathSegment::Key(key) => (0..self.items.len())
+                .filter(|x| self.items[*x].key.as_ref().is_some_and(|k| k.name == *key))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    // Adds an item at the end, laid out like the one before it
+    fn push(&mut self, key: Option<&str>, node: Node) {
+        let mut item = Item { before: String::new(), key: None, node, after: String::new() };
+        let mut key_layout = (String::new(), " ".to_string());
+        let single = self.items.len() == 1;
+        match self.items.last_mut() {
+            None => item.after = std::mem::take(&mut self.inner),
+            Some(last) => {
+                item.before = last.before.clone();
+                if single && item.before.is_empty() {
+                    item.before = " ".to_string();
+                }
+                // The line break before the closing bracket has to stay in front of it
+                if !self.trailing_comma && last.after.trim().is_empty() {
+                    item.after = std::mem::take(&mut last.after);
+                }
+                if let Some(key) = &last.key {
+                    key_layout = (key.before_colon.clone(), key.after_colon.clone());
+                }
+            }
+        }
+        item.key = key.map(|name| {
+            let mut text = String::new();
+            write_string(&mut text, name, false);
+            Key { text, name: name.to_string(), before_colon: key_layout.0, after_colon: key_layout.1 }
+        });
+        self.items.push(item);
+    }
+
+    fn remove(&mut self, index: usize) {
+        let item = self.items.remove(index);
+        if self.items.is_empty() {
+            self.trailing_comma = false;
+            self.inner = String::new();
+        } else if index == 0 {
+            self.items[0].before = item.before;
+        } else if index == self.items.len() && !self.trailing_comma {
+            self.items[index - 1].after.push_str(&item.after);
+        }
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>, brackets: (char, char)) -> fmt::Result {
+        write!(f, "{}", brackets.0)?;
+        for (i, item) in self.items.iter().enumerate() {
+            f.write_str(&item.before)?;
+            if let Some(key) = &item.key {
+                write!(f, "{}{}:{}", key.text, key.before_colon, key.after_colon)?;
+            }
+            item.node.write(f)?;
+            f.write_str(&item.after)?;
+            if i + 1 < self.items.len() || self.trailing_comma {
+                f.write_str(",")?;
+            }
+        }
+        write!(f, "{}{}", self.inner, brackets.1)
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f)
+    }
+}
+
+/// Writes the document back, byte for byte as it was parsed where nothing was edited.
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.before)?;
+        self.node.write(f)?;
+        f.write_str(&self.after)
+    }
+}
+
+// Splits source that the parser has already accepted into nodes, so it does not check for errors
+struct Builder<'a> {
+    source: &'a str,
+    position: usize,
+}
+
+impl Builder<'_> {
+    fn rest(&self) -> &str {
+        &self.source[self.position..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    // Takes the first `length` bytes of the rest
+    fn take(&mut self, length: usize) -> String {
+        let text = self.rest()[..length].to_string();
+        self.position += length;
+        text
+    }
+
+    // Whitespace and comments
+    fn trivia(&mut self) -> String {
+        let start = self.position;
+        loop {
+            let rest = self.rest();
+            if rest.starts_with("//") {
+                self.position += rest.find('\n').unwrap_or(rest.len());
+            } else if rest.starts_with("/*") {
+                self.position += rest.find("*/").map_or(rest.len(), |x| x + 2);
+            } else if let Some(c) = self.peek().filter(|x| x.is_whitespace() || *x == '\u{FEFF}') {
+                self.position += c.len_utf8();
+            } else {
+                break;
+            }
+        }
+        self.source[start..self.position].to_string()
+    }
+
+    fn node(&mut self) -> Node {
+        match self.peek() {
+            Some('[') => Node::Array(self.container(']', false)),
+            Some('{') => Node::Object(self.container('}', true)),
+            _ => Node::Scalar(self.scalar()),
+        }
+    }
+
+    fn container(&mut self, close: char, is_object: bool) -> Container {
+        self.position += 1;
+        let mut container = Container { is_object, ..Container::default() };
+        loop {
+            let before = self.trivia();
+            if self.peek() == Some(close) || self.peek().is_none() {
+                container.trailing_comma = !container.items.is_empty();
+                container.inner = before;
+                break;
+            }
+            let key = is_object.then(|| self.key());
+            let node = self.node();
+            let after = self.trivia();
+            container.items.push(Item { before, key, node, after });
+            if self.peek() != Some(',') {
+                break;
+            }
+            self.position += 1;
+        }
+        self.position += close.len_utf8();
+        container
+    }
+
+    fn key(&mut self) -> Key {
+        let text = if matches!(self.peek(), Some('"' | '\'')) {
+            self.scalar()
+        } else {
+            // A JSON5 identifier
+            let length = self.rest().find(|x: char| x == ':' || x == '/' || x.is_whitespace()).unwrap_or(self.rest().len());
+            self.take(length)
+        };
+        let name = match Node::Scalar(text.clone()).to_value() {
+            JsonValueType::JsonTypeString(name) => name,
+            _ => text.clone(),
+        };
+        let before_colon = self.trivia();
+        self.position += 1;
+        let after_colon = self.trivia();
+        Key { text, name, before_colon, after_colon }
+    }
+
+    // A string with its quotes, or a number or literal
+    fn scalar(&mut self) -> String {
+        let rest = self.rest();
+        let length = match self.peek() {
+            Some(quote @ ('"' | '\'')) => {
+                let mut escaped = false;
+                let end = rest.char_indices().skip(1).find(|(_, c)| {
+                    let found = !escaped && *c == quote;
+                    escaped = !escaped && *c == '\\';
+                    found
+                });
+                end.map_or(rest.len(), |(i, _)| i + 1)
+            }
+            _ => rest.find(|x: char| matches!(x, ',' | ']' | '}' | '/') || x.is_whitespace()).unwrap_or(rest.len()),
+        };
+        self.take(length)
+    }
+}
@@ -1,8 +1,361 @@
+use std::fmt;
+use super::path::Path;
+use super::span::Span;
+
+/// The kind of problem found while parsing.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ErrorKind {
+    /// The input ended while a value or token was still expected.
+    EndOfInput,
+
+    /// A character that can not start or continue the current construct.
+    UnexpectedCharacter,
+
+    /// A string is missing its closing quote.
+    UnterminatedString,
+
+    /// A block comment is missing its closing `*/`.
+    UnterminatedComment,
+
+    /// A backslash escape in a string is not valid.
+    InvalidEscape,
+
+    /// A control character appears unescaped inside a string.
+    ControlCharacter,
+
+    /// A string contains bytes that are not valid UTF-8.
+    InvalidUtf8,
+
+    /// A number does not follow the JSON number grammar.
+    InvalidNumber,
+
+    /// Something other than a string was found where an object key was expected.
+    ExpectedKey,
+
+    /// An object key is not followed by a `:`.
+    MissingColon,
+
+    /// An array element or object member is not followed by a `,` or the closing bracket.
+    MissingComma,
+
+    /// There is more than whitespace after the top-level value.
+    TrailingCharacters,
+
+    /// An object has the same key more than once.
+    DuplicateKey,
+
+    /// Arrays and objects are nested deeper than [`super::options::ParseOptions::max_depth`].
+    DepthLimitExceeded,
+
+    /// The input is longer than [`super::options::ParseOptions::max_input_size`].
+    InputTooLarge,
+
+    /// A string or key is longer than [`super::options::ParseOptions::max_string_length`].
+    StringTooLong,
+
+    /// An object has more members than [`super::options::ParseOptions::max_object_members`].
+    TooManyMembers,
+
+    /// An array has more elements than [`super::options::ParseOptions::max_array_length`].
+    ArrayTooLong,
+
+    /// The parse was stopped through [`super::progress::Progress::cancel`].
+    Cancelled,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            ErrorKind::EndOfInput => "unexpected end of input",
+            ErrorKind::UnexpectedCharacter => "unexpected character",
+            ErrorKind::UnterminatedString => "unterminated string",
+            ErrorKind::UnterminatedComment => "unterminated comment",
+            ErrorKind::InvalidEscape => "invalid escape sequence",
+            ErrorKind::ControlCharacter => "unescaped control character in string",
+            ErrorKind::InvalidUtf8 => "invalid UTF-8 in string",
+            ErrorKind::InvalidNumber => "invalid number",
+            ErrorKind::ExpectedKey => "expected an object key",
+            ErrorKind::MissingColon => "missing ':' after object key",
+            ErrorKind::MissingComma => "missing ',' or closing bracket",
+            ErrorKind::TrailingCharacters => "trailing characters after the document",
+            ErrorKind::DuplicateKey => "duplicate object key",
+            ErrorKind::DepthLimitExceeded => "nesting depth limit exceeded",
+            ErrorKind::InputTooLarge => "input size limit exceeded",
+            ErrorKind::StringTooLong => "string length limit exceeded",
+            ErrorKind::TooManyMembers => "object member limit exceeded",
+            ErrorKind::ArrayTooLong => "array length limit exceeded",
+            ErrorKind::Cancelled => "parsing was cancelled",
+        };
+        f.write_str(message)
+    }
+}
+
+/// A parse error with its location in the source.
 #[derive(Debug, PartialEq, Clone)]
-pub enum Error {
-    /// An unexpected end-of-line has been found.
-    EndOfLine,
+pub struct Error {
+    kind: ErrorKind,
+    offset: usize,
+    line: usize,
+    column: usize,
+    found: Option<char>,
+    expected: Vec<&'static str>,
+    snippet: String,
+}
+
+// Lines longer than this are cut down around the error position in the snippet
+const SNIPPET_WIDTH: usize = 80;
+
+impl Error {
+    /// Creates an error at byte `offset` of `source`, working out the line, column and snippet.
+    /// Invalid UTF-8 in the source is shown as replacement characters.
+    pub(crate) fn new(kind: ErrorKind, source: &[u8], offset: usize) -> Error {
+        let mut position = Position::START;
+        position.move_to(source, offset);
+        Error::at(kind, source, position)
+    }
+
+    // Like `new`, with the line and column already worked out
+    pub(crate) fn at(kind: ErrorKind, source: &[u8], position: Position) -> Error {
+        let offset = position.offset;
+        Error {
+            kind,
+            offset,
+            line: position.line,
+            column: position.column,
+            found: String::from_utf8_lossy(&source[offset..(offset + 4).min(source.len())]).chars().next(),
+            expected: Vec::new(),
+            snippet: render_snippet(source, offset, position.line),
+        }
+    }
+
+    // Moves an error found in `text`, the line of a larger source that starts at byte `offset`,
+    // to its place in that source, without going over the lines before it
+    pub(crate) fn on_line(mut self, text: &[u8], line: usize, offset: usize) -> Error {
+        self.snippet = render_snippet(text, self.offset, line);
+        self.offset += offset;
+        self.line = line;
+        self
+    }
+
+    /// Sets the list of tokens that would have been valid at the error position.
+    pub(crate) fn with_expected(mut self, expected: &[&'static str]) -> Error {
+        // Whatever the parser wanted where an unclosed comment starts, only its end can follow
+        if self.kind != ErrorKind::UnterminatedComment || self.expected.is_empty() {
+            self.expected = expected.to_vec();
+        }
+        self
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Byte offset of the error in the source.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// 1-based line of the error.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// 1-based column of the error, counted in characters.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// The character at the error position, `None` at the end of the input.
+    pub fn found(&self) -> Option<char> {
+        self.found
+    }
+
+    /// Descriptions of the tokens that would have been valid at the error position.
+    pub fn expected(&self) -> &[&'static str] {
+        &self.expected
+    }
+
+    /// The offending source line with a caret under the error position.
+    pub fn snippet(&self) -> &str {
+        &self.snippet
+    }
+}
+
+// A byte offset with its 1-based line and column, in characters. Moving it only goes over the
+// bytes in between, so a parse that reports many errors reads the source about once.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Position {
+    pub(crate) offset: usize,
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+}
+
+impl Position {
+    pub(crate) const START: Position = Position { offset: 0, line: 1, column: 1 };
+
+    pub(crate) fn move_to(&mut self, source: &[u8], offset: usize) {
+        let offset = offset.min(source.len());
+        if offset >= self.offset {
+            for byte in &source[self.offset..offset] {
+                if *byte == b'\n' {
+                    self.line += 1;
+                    self.column = 1;
+                } else if *byte & 0xC0 != 0x80 {
+                    // Continuation bytes belong to the character before
+                    self.column += 1;
+                }
+            }
+        } else {
+            let between = &source[offset..self.offset];
+            let lines = between.iter().filter(|x| **x == b'\n').count();
+            if lines == 0 {
+                self.column -= characters(between);
+            } else {
+                self.line -= lines;
+                let line_start = source[..offset].iter().rposition(|x| *x == b'\n').map_or(0, |x| x + 1);
+                self.column = characters(&source[line_start..offset]) + 1;
+            }
+        }
+        self.offset = offset;
+    }
+}
+
+fn characters(bytes: &[u8]) -> usize {
+    bytes.iter().filter(|x| **x & 0xC0 != 0x80).count()
+}
+
+// Shows the line around byte `offset` with a caret under it. Only the bytes that can end up in
+// the snippet are read, a line can be as long as the whole source.
+fn render_snippet(source: &[u8], offset: usize, line: usize) -> String {
+    // Enough bytes for the widest snippet, even if every character takes four of them
+    let reach = 4 * (SNIPPET_WIDTH + 1);
+    let back = &source[offset.saturating_sub(reach)..offset];
+    let line_start = back.iter().rposition(|x| *x == b'\n').map(|x| x + 1);
+    let cut_before = line_start.is_none() && offset > reach;
+    let mut before: Vec<char> = String::from_utf8_lossy(&back[line_start.unwrap_or(0)..]).chars().collect();
+    if cut_before {
+        // The first character may be a part of one
+        before.drain(..before.len().saturating_sub(SNIPPET_WIDTH));
+    }
+
+    let ahead = &source[offset..(offset + reach).min(source.len())];
+    let line_end = ahead.iter().position(|x| *x == b'\n');
+    let cut_after = line_end.is_none() && offset + reach < source.len();
+    let ahead = String::from_utf8_lossy(&ahead[..line_end.unwrap_or(ahead.len())]);
+    let ahead = if cut_after { &ahead } else { ahead.trim_end_matches('\r') };
+
+    let column = before.len() + 1;
+    let chars: Vec<char> = before.into_iter().chain(ahead.chars().take(SNIPPET_WIDTH + 1)).collect();
+    let mut start = 0;
+    let mut end = chars.len();
+    if chars.len() > SNIPPET_WIDTH {
+        start = column.saturating_sub(SNIPPET_WIDTH / 2).min(chars.len() - SNIPPET_WIDTH);
+        end = start + SNIPPET_WIDTH;
+    }
+
+    let mut text: String = chars[start..end].iter()
+        .map(|x| if *x == '\t' { ' ' } else { *x })
+        .collect();
+    let dots_before = start > 0 || cut_before;
+    if dots_before { text.insert_str(0, "..."); }
+    if end < chars.len() || cut_after { text.push_str("..."); }
+
+    let number = line.to_string();
+    let caret = column - 1 - start + if dots_before { 3 } else { 0 };
+    format!("{} | {}\n{} | {}^", number, text, " ".repeat(number.len()), " ".repeat(caret))
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at line {}, column {}", self.kind, self.line, self.column)?;
+        if let Some(found) = self.found.filter(|_| self.kind != ErrorKind::EndOfInput) {
+            write!(f, ", found {:?}", found)?;
+        }
+        if !self.expected.is_empty() {
+            write!(f, ", expected {}", self.expected.join(" or "))?;
+        }
+        write!(f, "\n{}", self.snippet)
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// How serious a [`Diagnostic`] is.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Severity {
+    /// The input is not valid, the value had to be repaired.
+    Error,
+
+    /// The input is valid but likely not what was meant, e.g. a repeated key.
+    Warning,
+}
+
+/// A problem that the recovering parser found and worked around.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Diagnostic {
+    pub error: Error,
+
+    /// The value in the recovered tree that the problem belongs to.
+    pub path: Path,
+
+    pub severity: Severity,
+
+    /// Another place in the source the problem involves, e.g. the first of two repeated keys.
+    pub related: Option<Span>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_error_location() {
+        let error = Error::new(ErrorKind::MissingColon, "{\n  \"é\" 1\n}".as_bytes(), 9);
+        assert_eq!(error.line(), 2);
+        assert_eq!(error.column(), 7);
+        assert_eq!(error.found(), Some('1'));
+        assert_eq!(error.snippet(), "2 |   \"é\" 1\n  |       ^");
+    }
+
+    #[test]
+    fn test_error_display() {
+        let error = Error::new(ErrorKind::MissingComma, b"[1 2]", 3).with_expected(&["','", "']'"]);
+        assert_eq!(error.to_string(), "missing ',' or closing bracket at line 1, column 4, found '2', expected ',' or ']'\n1 | [1 2]\n  |    ^");
+
+        let error = Error::new(ErrorKind::EndOfInput, b"[", 1);
+        assert_eq!(error.to_string(), "unexpected end of input at line 1, column 2\n1 | [\n  |  ^");
+    }
+
+    #[test]
+    fn test_error_long_line() {
+        let source = format!("{}b{}", "a".repeat(150), "a".repeat(49));
+        let error = Error::new(ErrorKind::UnexpectedCharacter, source.as_bytes(), 150);
+        let lines: Vec<&str> = error.snippet().lines().collect();
+        assert_eq!(lines[0].len(), "1 | ".len() + SNIPPET_WIDTH + 6);
+        let caret = lines[1].find('^').unwrap();
+        assert_eq!(&lines[0][caret..caret + 1], "b");
+    }
+
+    #[test]
+    fn test_error_very_long_line() {
+        let source = format!("{}é{}", "a".repeat(5000), "a".repeat(5000));
+        let error = Error::new(ErrorKind::UnexpectedCharacter, source.as_bytes(), 5000);
+        assert_eq!(error.column(), 5001);
+        let lines: Vec<&str> = error.snippet().lines().collect();
+        assert_eq!(lines[0].chars().count(), "1 | ".len() + SNIPPET_WIDTH + 6);
+        assert!(lines[0].starts_with("1 | ...") && lines[0].ends_with("..."));
+        assert_eq!(lines[0].chars().nth(lines[1].len() - 1), Some('é'));
+    }
 
-    /// A syntax error at the indicated cursor position has been found.
-    Character(usize),
-}
\ No newline at end of file
+    #[test]
+    fn test_position_moves_back() {
+        let source = "[1,\n  2,\n  é 3]".as_bytes();
+        let mut position = Position::START;
+        position.move_to(source, 14);
+        assert_eq!((position.line, position.column), (3, 5));
+        position.move_to(source, 11);
+        assert_eq!((position.line, position.column), (3, 3));
+        position.move_to(source, 6);
+        assert_eq!((position.line, position.column), (2, 3));
+    }
+}
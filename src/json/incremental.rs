@@ -0,0 +1,249 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+use super::error::Error;
+use super::object_parser;
+use super::options::{DuplicateKeys, ParseOptions};
+use super::path::Path;
+use super::span::{NodeSpan, Span, Spans};
+use super::state::State;
+use super::value::JsonValueType;
+
+/// A change of the source text, the bytes in `range` are replaced by `text`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+/// How much of the document [`Tree::apply`] had to read again.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Reparsed {
+    /// Only the array or object at this path.
+    Container(Path),
+
+    /// The whole document.
+    Full,
+}
+
+/// A parsed document that is kept up to date while its text is edited.
+///
+/// An edit inside of an array or object, that leaves its brackets alone, only parses that
+/// container again. When the container no longer parses on its own, e.g. because a bracket or
+/// quote was added, the whole document is parsed.
+///
+/// ```
+/// use json_editor::json::incremental::{Reparsed, TextEdit, Tree};
+/// use json_editor::json::options::ParseOptions;
+/// use json_editor::json::path::{Path, PathSegment};
+///
+/// let mut tree = Tree::new("{\"a\" : [1, 2], \"b\" : 3}".to_string(), &ParseOptions::default()).unwrap();
+/// let edit = TextEdit { range: 12..12, text: ", 5".to_string() };
+/// let path = Path::root().join(PathSegment::Key("a".to_string()));
+/// assert_eq!(tree.apply(&edit), Ok(Reparsed::Container(path)));
+/// assert_eq!(tree.source(), "{\"a\" : [1, 2, 5], \"b\" : 3}");
+/// ```
+pub struct Tree {
+    source: String,
+    value: Option<JsonValueType>,
+    spans: Spans,
+    // The path of every span by where its value starts, so that an edit only looks at the
+    // container it is in, the containers around it and what comes after it
+    starts: BTreeMap<usize, Path>,
+    options: ParseOptions,
+}
+
+impl Tree {
+    pub fn new(source: String, options: &ParseOptions) -> Result<Tree, Error> {
+        let mut tree = Tree { source, value: None, spans: Spans::default(), starts: BTreeMap::new(), options: options.clone() };
+        tree.parse()?;
+        Ok(tree)
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The value of the source, `None` while the source is not valid.
+    pub fn value(&self) -> Option<&JsonValueType> {
+        self.value.as_ref()
+    }
+
+    /// Where the values are in the source, as from [`super::to_object_with_spans`].
+    pub fn spans(&self) -> &Spans {
+        &self.spans
+    }
+
+    /// Changes the source and parses what is needed to bring the value up to date.
+    ///
+    /// When the new source is not valid the error is returned and the value is `None` until a
+    /// later edit makes the source valid again. Panics if the range is not on character
+    /// boundaries of the source.
+    pub fn apply(&mut self, edit: &TextEdit) -> Result<Reparsed, Error> {
+        let container = self.enclosing(&edit.range);
+        // Only needed in the coordinates of the old source, before the edit is made
+        let old_end = container.as_ref().map(|(_, span)| advance(self.source.as_bytes(), &span.value, edit.range.end));
+        self.source.replace_range(edit.range.clone(), &edit.text);
+
+        if let (Some((path, span)), Some(old_end)) = (container, old_end) {
+            if self.reparse_container(&path, &span, edit, old_end) {
+                return Ok(Reparsed::Container(path));
+            }
+        }
+        self.parse()?;
+        Ok(Reparsed::Full)
+    }
+
+    fn parse(&mut self) -> Result<(), Error> {
+        self.value = None;
+        self.spans = Spans::default();
+        self.starts.clear();
+        let mut state = State::new(self.source.as_bytes()).with_spans().with_options(&self.options);
+        let value = object_parser::parse_document(&mut state)?;
+        self.spans = state.take_spans();
+        self.starts = self.spans.iter().map(|(path, node)| (node.value.start, path.clone())).collect();
+        self.value = Some(value);
+        Ok(())
+    }
+
+    // The innermost array or object that holds the edit between its brackets
+    fn enclosing(&self, range: &Range<usize>) -> Option<(Path, NodeSpan)> {
+        // Repeated keys that are kept together in an array have no spans of their own
+        if self.value.is_none() || self.options.duplicate_keys == DuplicateKeys::KeepAll {
+            return None;
+        }
+        // The value that starts last before the edit is in that container, or is the container
+        let (_, nearest) = self.starts.range(..range.start).next_back()?;
+        let source = self.source.as_bytes();
+        (0..=nearest.0.len()).rev()
+            .map(|length| Path(nearest.0[..length].to_vec()))
+            .find_map(|path| {
+                let span = *self.spans.get(&path)?;
+                let holds = range.end < span.value.end && matches!(source[span.value.start], b'[' | b'{');
+                holds.then_some((path, span))
+            })
+    }
+
+    // Returns false when the container can not be read on its own, `old_end` is the line and
+    // column where the edit ended in the old source
+    fn reparse_container(&mut self, path: &Path, span: &NodeSpan, edit: &TextEdit, old_end: (usize, usize)) -> bool {
+        let start = span.value.start;
+        let end = span.value.end + edit.text.len() - edit.range.len();
+        if self.source.len() > self.options.max_input_size {
+            return false;
+        }
+
+        // The container is as deep in the slice as the top-level value
+        let options = self.options.clone().with_max_depth(self.options.max_depth.saturating_sub(path.0.len()));
+        let mut state = State::new(&self.source.as_bytes()[start..end]).with_spans().with_options(&options);
+        // The container has to end where the slice does. Anything after it, even a comment,
+        // means the edit moved its end, e.g. by commenting out its closing bracket.
+        let value = match object_parser::parse_value(&mut state) {
+            Ok(value) if state.at_end() => value,
+            _ => return false,
+        };
+        let Some(target) = self.value.as_mut().and_then(|x| value_at_mut(x, path)) else {
+            return false;
+        };
+        *target = value;
+
+        let new_end = advance(self.source.as_bytes(), &span.value, edit.range.start + edit.text.len());
+        let shift = Shift {
+            from: edit.range.end,
+            delta: edit.text.len() as isize - edit.range.len() as isize,
+            line: old_end.0,
+            lines: new_end.0 as isize - old_end.0 as isize,
+            columns: new_end.1 as isize - old_end.1 as isize,
+        };
+        // The container and everything in it is replaced
+        let replaced: Vec<usize> = self.starts.range(span.value.start..span.value.end).map(|(x, _)| *x).collect();
+        for start in replaced {
+            if let Some(x) = self.starts.remove(&start) {
+                self.spans.remove(&x);
+            }
+        }
+        // The containers around it end later or earlier
+        for length in 0..path.0.len() {
+            if let Some(node) = self.spans.get_mut(&Path(path.0[..length].to_vec())) {
+                shift.apply(&mut node.value);
+            }
+        }
+        // And everything after it moves
+        for (_, x) in self.starts.split_off(&shift.from) {
+            let Some(node) = self.spans.get_mut(&x) else { continue };
+            shift.apply(&mut node.value);
+            if let Some(key) = &mut node.key {
+                shift.apply(key);
+            }
+            self.starts.insert(node.value.start, x);
+        }
+
+        for (child, node) in state.take_spans().iter() {
+            let mut node = *node;
+            move_to(&mut node.value, &span.value);
+            if let Some(key) = node.key.as_mut() {
+                move_to(key, &span.value);
+            }
+            if child.is_root() {
+                node.key = span.key;
+            }
+            let child = Path([&path.0[..], &child.0[..]].concat());
+            self.starts.insert(node.value.start, child.clone());
+            self.spans.insert(child, node);
+        }
+        true
+    }
+}
+
+// How the spans after an edit move
+struct Shift {
+    // End of the edit in the old source
+    from: usize,
+    delta: isize,
+    // Line of `from`, spans that start on it also move by `columns`
+    line: usize,
+    lines: isize,
+    columns: isize,
+}
+
+impl Shift {
+    fn apply(&self, span: &mut Span) {
+        if span.start >= self.from {
+            span.start = span.start.saturating_add_signed(self.delta);
+            if span.line == self.line {
+                span.column = span.column.saturating_add_signed(self.columns);
+            }
+            span.line = span.line.saturating_add_signed(self.lines);
+        }
+        if span.end >= self.from {
+            span.end = span.end.saturating_add_signed(self.delta);
+        }
+    }
+}
+
+// Moves a span of the reparsed container, which starts at line 1 column 1, to where the container is
+fn move_to(span: &mut Span, container: &Span) {
+    span.start += container.start;
+    span.end += container.start;
+    if span.line == 1 {
+        span.column += container.column - 1;
+    }
+    span.line += container.line - 1;
+}
+
+// Line and column of `offset`, counted from the start of `span`
+fn advance(source: &[u8], span: &Span, offset: usize) -> (usize, usize) {
+    let (mut line, mut column) = (span.line, span.column);
+    for byte in &source[span.start..offset] {
+        if *byte == b'\n' {
+            line += 1;
+            column = 1;
+        } else if *byte & 0xC0 != 0x80 {
+            column += 1;
+        }
+    }
+    (line, column)
+}
+
+fn value_at_mut<'a>(value: &'a mut JsonValueType, path: &Path) -> Option<&'a mut JsonValueType> {
+    path.0.iter().try_fold(value, |value, segment| value.get_mut(segment))
+}
@@ -0,0 +1,349 @@
+use std::fs::File;
+use std::io;
+use memmap2::Mmap;
+use super::error::{Error, ErrorKind};
+use super::object_parser;
+use super::options::{Dialect, ParseOptions};
+use super::progress::{Progress, REPORT_INTERVAL};
+use super::state::State;
+use super::value::JsonValueType;
+
+/// An array or object in the structural index of a [`LazyDocument`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Container {
+    /// Byte offset of the opening bracket.
+    pub start: usize,
+
+    /// Byte offset just past the closing bracket.
+    pub end: usize,
+
+    pub is_object: bool,
+
+    /// Number of elements or members.
+    pub len: usize,
+}
+
+/// A value of a [`LazyDocument`], scalars are read right away while arrays and objects are
+/// only read when asked for.
+#[derive(Debug, PartialEq, Clone)]
+pub enum LazyValue {
+    Scalar(JsonValueType),
+    Container(Container),
+}
+
+enum Source {
+    Mapped(Mmap),
+    Owned(Vec<u8>),
+}
+
+impl Source {
+    fn bytes(&self) -> &[u8] {
+        match self {
+            Source::Mapped(map) => map,
+            Source::Owned(bytes) => bytes,
+        }
+    }
+}
+
+/// A document that is only read as far as it is looked at, for files too large to parse in one
+/// go.
+///
+/// Opening it makes a single pass over the input that records where every array and object is
+/// and how many children it has. The children of a container are read by
+/// [`LazyDocument::children`] and whole subtrees by [`LazyDocument::value`]. Errors inside of
+/// strings, numbers and between the brackets are only found when that part is read.
+///
+/// ```
+/// use json_editor::json::lazy::{LazyDocument, LazyValue};
+/// use json_editor::json::options::ParseOptions;
+///
+/// let document = LazyDocument::from_bytes(b"{\"a\" : [1, 2], \"b\" : true}".to_vec(), &ParseOptions::default()).unwrap();
+/// let LazyValue::Container(root) = document.root().unwrap() else { panic!() };
+/// assert_eq!(root.len, 2);
+/// let (key, _) = document.children(&root).next().unwrap().unwrap();
+/// assert_eq!(key.as_deref(), Some("a"));
+/// ```
+pub struct LazyDocument {
+    source: Source,
+    // In the order of their opening brackets
+    index: Vec<Container>,
+    root: usize,
+    options: ParseOptions,
+}
+
+impl LazyDocument {
+    /// Maps the file at `path` into memory and indexes it. A file that can not be indexed is
+    /// reported as [`io::ErrorKind::InvalidData`] with the parse [`Error`] inside.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be changed or truncated, by this or any other process, while the
+    /// document is open. The document reads the mapped memory as it is, and a change to the file
+    /// is undefined behavior.
+    pub unsafe fn open(path: &std::path::Path, options: &ParseOptions) -> io::Result<LazyDocument> {
+        Self::open_with_progress(path, options, &Progress::new())
+    }
+
+    /// Like [`LazyDocument::open`], and reports how far the indexing has got through `progress`.
+    /// Fails with [`ErrorKind::Cancelled`] inside the error if it is cancelled through `progress`.
+    ///
+    /// # Safety
+    ///
+    /// See [`LazyDocument::open`].
+    pub unsafe fn open_with_progress(path: &std::path::Path, options: &ParseOptions, progress: &Progress) -> io::Result<LazyDocument> {
+        let file = File::open(path)?;
+        // Empty files can not be mapped on every platform
+        let source = if file.metadata()?.len() == 0 {
+            Source::Owned(Vec::new())
+        } else {
+            // Safety: the map is only read, and the caller makes sure the file is not changed
+            Source::Mapped(unsafe { Mmap::map(&file)? })
+        };
+        Self::new(source, options, Some(progress)).map_err(|x| io::Error::new(io::ErrorKind::InvalidData, x))
+    }
+
+    /// Indexes UTF-8 encoded `content` that is already in memory.
+    pub fn from_bytes(content: Vec<u8>, options: &ParseOptions) -> Result<LazyDocument, Error> {
+        Self::new(Source::Owned(content), options, None)
+    }
+
+    fn new(source: Source, options: &ParseOptions, progress: Option<&Progress>) -> Result<LazyDocument, Error> {
+        let bytes = source.bytes();
+        if bytes.len() > options.max_input_size {
+            return Err(Error::new(ErrorKind::InputTooLarge, bytes, options.max_input_size));
+        }
+        let (index, root) = build_index(bytes, options, progress)?;
+        Ok(LazyDocument { source, index, root, options: options.clone() })
+    }
+
+    /// Size of the input in bytes.
+    pub fn len(&self) -> usize {
+        self.source.bytes().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of arrays and objects in the document.
+    pub fn containers(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn root(&self) -> Result<LazyValue, Error> {
+        let mut state = self.state(self.root);
+        self.read_value(&mut state)
+    }
+
+    /// The elements of an array or the members of an object with their keys, in source order.
+    /// Children are read as the iterator advances, use `skip` and `take` to read a page of them.
+    pub fn children(&self, container: &Container) -> Children<'_> {
+        Children { document: self, state: self.state(container.start + 1), container: *container, read: 0 }
+    }
+
+    /// The children of `container` from where an earlier iterator over them got to, as given by
+    /// [`Children::position`].
+    pub fn children_from(&self, container: &Container, position: ChildrenPosition) -> Children<'_> {
+        Children { document: self, state: self.state(position.offset), container: *container, read: position.read }
+    }
+
+    /// Reads the whole subtree of `container`.
+    pub fn value(&self, container: &Container) -> Result<JsonValueType, Error> {
+        object_parser::parse_value(&mut self.state(container.start))
+    }
+
+    fn state(&self, position: usize) -> State<'_> {
+        let mut state = State::new(self.source.bytes()).with_options(&self.options);
+        state.skip_to(position);
+        state
+    }
+
+    // Containers are skipped using the index
+    fn read_value(&self, state: &mut State) -> Result<LazyValue, Error> {
+        let start = state.cursor();
+        if matches!(state.peek(), Some(b'[' | b'{')) {
+            let position = self.index.binary_search_by_key(&start, |x| x.start).map_err(|_| state.error(ErrorKind::UnexpectedCharacter))?;
+            let container = self.index[position];
+            state.skip_to(container.end);
+            return Ok(LazyValue::Container(container));
+        }
+        object_parser::parse_value(state).map(LazyValue::Scalar)
+    }
+}
+
+/// How far a [`Children`] iterator has read, to carry on from there with
+/// [`LazyDocument::children_from`] without reading the earlier children again.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ChildrenPosition {
+    offset: usize,
+    read: usize,
+}
+
+/// Iterator over the children of a container, see [`LazyDocument::children`].
+pub struct Children<'a> {
+    document: &'a LazyDocument,
+    state: State<'a>,
+    container: Container,
+    read: usize,
+}
+
+impl Children<'_> {
+    /// Where the next child is read from, see [`ChildrenPosition`].
+    pub fn position(&self) -> ChildrenPosition {
+        ChildrenPosition { offset: self.state.cursor(), read: self.read }
+    }
+
+    fn read_child(&mut self) -> Result<(Option<String>, LazyValue), Error> {
+        let state = &mut self.state;
+        let key = if self.container.is_object {
+            let key = object_parser::parse_key(state)?.unwrap_or_default().into_owned();
+            if !state.read_char(b':') {
+                return Err(state.error(ErrorKind::MissingColon).with_expected(&["':'"]));
+            }
+            Some(key)
+        } else {
+            None
+        };
+        state.skip_whitespace();
+        let value = self.document.read_value(state)?;
+        let close = if self.container.is_object { b'}' } else { b']' };
+        if !state.read_char(b',') && state.peek() != Some(close) {
+            return Err(state.error(ErrorKind::MissingComma).with_expected(&["','"]));
+        }
+        Ok((key, value))
+    }
+}
+
+impl Iterator for Children<'_> {
+    type Item = Result<(Option<String>, LazyValue), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.read >= self.container.len {
+            return None;
+        }
+        let result = self.read_child();
+        // Nothing after an error can be trusted
+        self.read = if result.is_ok() { self.read + 1 } else { self.container.len };
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.container.len - self.read))
+    }
+}
+
+impl std::iter::FusedIterator for Children<'_> {}
+
+// One pass over the input that only looks at brackets, commas, strings and comments. Returns the
+// containers and the offset of the top-level value.
+fn build_index(bytes: &[u8], options: &ParseOptions, progress: Option<&Progress>) -> Result<(Vec<Container>, usize), Error> {
+    if let Some(progress) = progress {
+        progress.set_total(bytes.len());
+    }
+    // Position at which the progress is updated next
+    let mut next_report = if progress.is_some() { 0 } else { usize::MAX };
+    let comments = options.dialect.allows_comments();
+    let json5 = options.dialect == Dialect::Json5;
+    let mut index: Vec<Container> = Vec::new();
+    // Open containers, and whether the next value is a new child
+    let mut stack: Vec<(usize, bool)> = Vec::new();
+    let mut root = None;
+    let mut position = 0;
+    while position < bytes.len() {
+        if let Some(progress) = progress.filter(|_| position >= next_report) {
+            progress.set_done(position);
+            next_report = position + REPORT_INTERVAL;
+            if progress.is_cancelled() {
+                return Err(Error::new(ErrorKind::Cancelled, bytes, position));
+            }
+        }
+        let c = bytes[position];
+        if matches!(c, b' ' | b'\t' | b'\n' | b'\r' | 0x0B | 0x0C) {
+            position += 1;
+            continue;
+        }
+        if comments && c == b'/' {
+            match bytes.get(position + 1) {
+                Some(b'/') => {
+                    position = bytes[position..].iter().position(|x| *x == b'\n').map_or(bytes.len(), |x| position + x);
+                    continue;
+                }
+                Some(b'*') => {
+                    let end = bytes[position + 2..].windows(2).position(|x| x == b"*/")
+                        .ok_or_else(|| Error::new(ErrorKind::UnterminatedComment, bytes, position))?;
+                    position += end + 4;
+                    continue;
+                }
+                _ => {}
+            }
+        }
+
+        match stack.last_mut() {
+            None if root.is_some() => {
+                return Err(Error::new(ErrorKind::TrailingCharacters, bytes, position).with_expected(&["end of input"]));
+            }
+            None => root = Some(position),
+            Some((container, expect_child)) => {
+                if *expect_child && !matches!(c, b']' | b'}') {
+                    index[*container].len += 1;
+                    *expect_child = false;
+                }
+            }
+        }
+
+        match c {
+            b'[' | b'{' => {
+                if stack.len() >= options.max_depth {
+                    return Err(Error::new(ErrorKind::DepthLimitExceeded, bytes, position));
+                }
+                stack.push((index.len(), true));
+                index.push(Container { start: position, end: 0, is_object: c == b'{', len: 0 });
+                position += 1;
+            }
+            b']' | b'}' => {
+                match stack.pop() {
+                    Some((container, _)) if index[container].is_object == (c == b'}') => index[container].end = position + 1,
+                    _ => return Err(Error::new(ErrorKind::UnexpectedCharacter, bytes, position)),
+                }
+                position += 1;
+            }
+            b',' => {
+                if let Some((_, expect_child)) = stack.last_mut() {
+                    *expect_child = true;
+                }
+                position += 1;
+            }
+            b'"' => position = skip_string(bytes, position)?,
+            b'\'' if json5 => position = skip_string(bytes, position)?,
+            b':' => position += 1,
+            // Numbers, literals and identifier keys
+            _ => {
+                position += 1;
+                while position < bytes.len() && !b" \t\n\r,:[]{}\"/".contains(&bytes[position]) {
+                    position += 1;
+                }
+            }
+        }
+    }
+    if !stack.is_empty() || root.is_none() {
+        return Err(Error::new(ErrorKind::EndOfInput, bytes, bytes.len()).with_expected(&["value"]));
+    }
+    if let Some(progress) = progress {
+        progress.set_done(bytes.len());
+    }
+    Ok((index, root.unwrap_or_default()))
+}
+
+// Returns the position after the closing quote
+fn skip_string(bytes: &[u8], start: usize) -> Result<usize, Error> {
+    let quote = bytes[start];
+    let mut position = start + 1;
+    while position < bytes.len() {
+        match bytes[position] {
+            b'\\' => position += 2,
+            x if x == quote => return Ok(position + 1),
+            _ => position += 1,
+        }
+    }
+    Err(Error::new(ErrorKind::UnterminatedString, bytes, start))
+}
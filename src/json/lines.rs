@@ -0,0 +1,85 @@
+use super::error::Error;
+use super::object_parser;
+use super::options::ParseOptions;
+use super::state::State;
+use super::value::JsonValueType;
+
+/// A document read from one line of a JSON Lines file.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Line {
+    /// 1-based line number in the file.
+    pub number: usize,
+
+    /// Byte offset of the start of the line.
+    pub offset: usize,
+
+    pub value: JsonValueType,
+}
+
+/// Reads a [JSON Lines](https://jsonlines.org) or NDJSON file one record at a time.
+///
+/// Each line holds one document, blank lines are skipped. A line that can not be read yields its
+/// [`Error`], with the line and offset in the whole file, and reading carries on with the next
+/// line, so bad lines can be skipped with `filter_map(Result::ok)`.
+///
+/// ```
+/// use json_editor::json::lines;
+///
+/// let records: Vec<_> = lines("{\"id\" : 1}\n{\"id\" :\n\n{\"id\" : 3}\n").collect();
+/// assert_eq!(records.len(), 3);
+/// assert_eq!(records[1].as_ref().unwrap_err().line(), 2);
+/// assert_eq!(records[2].as_ref().unwrap().number, 4);
+/// ```
+pub struct Lines<'a> {
+    content: &'a [u8],
+    // Start of the next line, past the end when all lines have been read
+    offset: usize,
+    number: usize,
+    options: ParseOptions,
+}
+
+impl<'a> Lines<'a> {
+    pub fn new(content: &'a str) -> Self {
+        Self::from_bytes(content.as_bytes())
+    }
+
+    /// Reads UTF-8 encoded `content`, see [`super::to_object_bytes`].
+    pub fn from_bytes(content: &'a [u8]) -> Self {
+        Self { content, offset: 0, number: 0, options: ParseOptions::default() }
+    }
+
+    /// Reads every line with `options`, the input size limit applies to each line.
+    pub fn with_options(mut self, options: &ParseOptions) -> Self {
+        self.options = options.clone();
+        self
+    }
+}
+
+impl Iterator for Lines<'_> {
+    type Item = Result<Line, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.offset <= self.content.len() {
+            let start = self.offset;
+            let end = self.content[start..].iter().position(|x| *x == b'\n').map_or(self.content.len(), |x| start + x);
+            self.offset = end + 1;
+            self.number += 1;
+
+            // The state only holds the line, so a document can not run on into the next one and
+            // errors are located without going over the lines before it
+            let text = &self.content[start..end];
+            let mut state = State::new(text).with_options(&self.options);
+            if state.skip_whitespace() {
+                continue;
+            }
+            state.skip_to(0);
+            let number = self.number;
+            return Some(object_parser::parse_document(&mut state)
+                .map(|value| Line { number, offset: start, value })
+                .map_err(|x| x.on_line(text, number, start)));
+        }
+        None
+    }
+}
+
+impl std::iter::FusedIterator for Lines<'_> {}
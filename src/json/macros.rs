@@ -0,0 +1,184 @@
+/// Builds a [`JsonValueType`](crate::json::value::JsonValueType) from JSON syntax.
+///
+/// Values that are not JSON literals, arrays or objects are Rust expressions converted with
+/// `From`, so variables and calls can be used anywhere a value goes. Keys are string literals,
+/// or any expression that gives a `String` or `&str` when put in parentheses. Object members keep
+/// the order they are written in, and a trailing comma is allowed.
+///
+/// Each element or member whose value is more than one token, like `-1` or `a + b`, takes a
+/// step of macro recursion, so an array or object with more than about a hundred of them needs
+/// a higher `recursion_limit`.
+///
+/// ```
+/// use json_editor::json;
+///
+/// let name = "example";
+/// let value = json!({
+///     "name": name,
+///     "version": 2,
+///     "tags": ["a", null, [1.5, false]],
+///     (format!("{}_count", name)): name.len(),
+/// });
+/// assert_eq!(value.to_string(), r#"{"name" : "example", "version" : 2, "tags" : ["a", null, [1.5, false]], "example_count" : 7}"#);
+/// ```
+#[macro_export]
+macro_rules! json {
+    (null) => {
+        $crate::json::value::JsonValueType::JsonTypeNull
+    };
+    (true) => {
+        $crate::json::value::JsonValueType::JsonTypeBool(true)
+    };
+    (false) => {
+        $crate::json::value::JsonValueType::JsonTypeBool(false)
+    };
+    ([]) => {
+        $crate::json::value::JsonValueType::JsonTypeArray($crate::json::Array(::std::vec::Vec::new()))
+    };
+    // Elements that are single tokens need no munching, so long arrays do not run into the
+    // recursion limit
+    ([ $($value:tt),+ $(,)? ]) => {
+        $crate::json::value::JsonValueType::JsonTypeArray($crate::json::Array(::std::vec![$($crate::json!($value)),+]))
+    };
+    ([ $($tt:tt)+ ]) => {
+        $crate::json::value::JsonValueType::JsonTypeArray($crate::json::Array($crate::json_internal!(@array [] $($tt)+)))
+    };
+    ({}) => {
+        $crate::json::value::JsonValueType::JsonTypeObject($crate::json::Object::new())
+    };
+    // The same for objects whose values are single tokens, keys always are
+    ({ $($key:tt : $value:tt),+ $(,)? }) => {{
+        let mut object = $crate::json::Object::new();
+        $(object.0.insert(::std::string::String::from($key), $crate::json!($value));)+
+        $crate::json::value::JsonValueType::JsonTypeObject(object)
+    }};
+    ({ $($tt:tt)+ }) => {{
+        let mut object = $crate::json::Object::new();
+        $crate::json_internal!(@object object [] () ($($tt)+));
+        $crate::json::value::JsonValueType::JsonTypeObject(object)
+    }};
+    ($other:expr) => {
+        $crate::json::value::JsonValueType::from($other)
+    };
+}
+
+// Token munchers behind `json!`. Arrays collect their elements as expressions, objects take the
+// tokens of a key up to its colon and then the tokens of the value up to the next comma. Objects
+// collect their insert statements and expand them all at the end.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! json_internal {
+    // Arrays, done
+    (@array [$($elements:expr,)*]) => {
+        ::std::vec![$($elements,)*]
+    };
+    (@array [$($elements:expr),*]) => {
+        ::std::vec![$($elements),*]
+    };
+
+    // Arrays, elements that are not expressions
+    (@array [$($elements:expr,)*] null $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elements,)* $crate::json!(null)] $($rest)*)
+    };
+    (@array [$($elements:expr,)*] true $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elements,)* $crate::json!(true)] $($rest)*)
+    };
+    (@array [$($elements:expr,)*] false $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elements,)* $crate::json!(false)] $($rest)*)
+    };
+    (@array [$($elements:expr,)*] [$($array:tt)*] $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elements,)* $crate::json!([$($array)*])] $($rest)*)
+    };
+    (@array [$($elements:expr,)*] {$($object:tt)*} $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elements,)* $crate::json!({$($object)*})] $($rest)*)
+    };
+
+    // Arrays, expressions
+    (@array [$($elements:expr,)*] $next:expr, $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elements,)* $crate::json!($next),] $($rest)*)
+    };
+    (@array [$($elements:expr,)*] $last:expr) => {
+        $crate::json_internal!(@array [$($elements,)* $crate::json!($last)])
+    };
+
+    // Arrays, the comma after an element that is not an expression
+    (@array [$($elements:expr),*] , $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elements,)*] $($rest)*)
+    };
+
+    // Arrays, anything else
+    (@array [$($elements:expr),*] $unexpected:tt $($rest:tt)*) => {
+        ::std::compile_error!(::std::concat!("unexpected `", ::std::stringify!($unexpected), "` in json! array"))
+    };
+
+    // Objects, done
+    (@object $object:ident [$($done:tt)*] () ()) => {
+        $($done)*
+    };
+
+    // Objects, a member in one step when its value is a single token or an expression
+    (@object $object:ident [$($done:tt)*] () ($key:tt : $value:tt , $($rest:tt)*)) => {
+        $crate::json_internal!(@object $object [$($done)* $object.0.insert(::std::string::String::from($key), $crate::json!($value));] () ($($rest)*))
+    };
+    (@object $object:ident [$($done:tt)*] () ($key:tt : $value:tt)) => {
+        $crate::json_internal!(@object $object [$($done)* $object.0.insert(::std::string::String::from($key), $crate::json!($value));] () ())
+    };
+
+    (@object $object:ident [$($done:tt)*] () ($key:tt : $value:expr , $($rest:tt)*)) => {
+        $crate::json_internal!(@object $object [$($done)* $object.0.insert(::std::string::String::from($key), $crate::json!($value));] () ($($rest)*))
+    };
+    (@object $object:ident [$($done:tt)*] () ($key:tt : $value:expr)) => {
+        $crate::json_internal!(@object $object [$($done)* $object.0.insert(::std::string::String::from($key), $crate::json!($value));] () ())
+    };
+
+    // Objects, a member with its value read
+    (@object $object:ident [$($done:tt)*] [$($key:tt)+] ($value:expr) , $($rest:tt)*) => {
+        $crate::json_internal!(@object $object [$($done)* $object.0.insert(::std::string::String::from($($key)+), $value);] () ($($rest)*))
+    };
+    (@object $object:ident [$($done:tt)*] [$($key:tt)+] ($value:expr)) => {
+        $crate::json_internal!(@object $object [$($done)* $object.0.insert(::std::string::String::from($($key)+), $value);] () ())
+    };
+
+    // Objects, values that are not expressions
+    (@object $object:ident [$($done:tt)*] ($($key:tt)+) (: null $($rest:tt)*)) => {
+        $crate::json_internal!(@object $object [$($done)*] [$($key)+] ($crate::json!(null)) $($rest)*)
+    };
+    (@object $object:ident [$($done:tt)*] ($($key:tt)+) (: true $($rest:tt)*)) => {
+        $crate::json_internal!(@object $object [$($done)*] [$($key)+] ($crate::json!(true)) $($rest)*)
+    };
+    (@object $object:ident [$($done:tt)*] ($($key:tt)+) (: false $($rest:tt)*)) => {
+        $crate::json_internal!(@object $object [$($done)*] [$($key)+] ($crate::json!(false)) $($rest)*)
+    };
+    (@object $object:ident [$($done:tt)*] ($($key:tt)+) (: [$($array:tt)*] $($rest:tt)*)) => {
+        $crate::json_internal!(@object $object [$($done)*] [$($key)+] ($crate::json!([$($array)*])) $($rest)*)
+    };
+    (@object $object:ident [$($done:tt)*] ($($key:tt)+) (: {$($map:tt)*} $($rest:tt)*)) => {
+        $crate::json_internal!(@object $object [$($done)*] [$($key)+] ($crate::json!({$($map)*})) $($rest)*)
+    };
+
+    // Objects, expressions
+    (@object $object:ident [$($done:tt)*] ($($key:tt)+) (: $value:expr , $($rest:tt)*)) => {
+        $crate::json_internal!(@object $object [$($done)*] [$($key)+] ($crate::json!($value)) , $($rest)*)
+    };
+    (@object $object:ident [$($done:tt)*] ($($key:tt)+) (: $value:expr)) => {
+        $crate::json_internal!(@object $object [$($done)*] [$($key)+] ($crate::json!($value)))
+    };
+
+    // Objects, a key without a value
+    (@object $object:ident [$($done:tt)*] ($($key:tt)+) (:)) => {
+        ::std::compile_error!("missing value after `:` in json! object")
+    };
+    (@object $object:ident [$($done:tt)*] ($($key:tt)+) ()) => {
+        ::std::compile_error!("missing `:` after a key in json! object")
+    };
+
+    // Objects, a key in parentheses is an expression
+    (@object $object:ident [$($done:tt)*] () (($key:expr) : $($rest:tt)*)) => {
+        $crate::json_internal!(@object $object [$($done)*] ($key) (: $($rest)*))
+    };
+
+    // Objects, the next token of the key
+    (@object $object:ident [$($done:tt)*] ($($key:tt)*) ($tt:tt $($rest:tt)*)) => {
+        $crate::json_internal!(@object $object [$($done)*] ($($key)* $tt) ($($rest)*))
+    };
+}
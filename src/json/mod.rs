@@ -1,35 +1,227 @@
-use std::collections::HashMap;
-use std::hash::Hasher;
-use error::Error;
+use indexmap::IndexMap;
+use error::{Diagnostic, Error, ErrorKind};
 use crate::json::value::JsonValueType;
 
 mod object_parser;
+mod macros;
 mod state;
 pub mod value;
+pub mod value_ref;
+pub mod number;
 pub mod error;
+pub mod path;
+pub mod reader;
+pub mod span;
+pub mod options;
+pub mod comments;
+pub mod serializer;
+pub mod lines;
+pub mod cst;
+pub mod incremental;
+pub mod lazy;
+pub mod progress;
 
+/// The elements of a JSON array.
+///
+/// Arrays and objects implement `Drop` so that deep trees are dropped without recursion, which
+/// means they can not be taken apart by moving out of them as in `let Array(values) = array;`.
+/// Use [`Array::into_vec`] and [`Object::into_map`] for that.
 #[derive(Debug, PartialEq, Clone)]
 pub struct Array(pub Vec<JsonValueType>);
 
-impl std::hash::Hash for Array {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        let ptr = self as *const Array;
-        let addr = ptr as usize;
-        state.write_usize(addr);
+impl Array {
+    pub fn into_vec(mut self) -> Vec<JsonValueType> {
+        std::mem::take(&mut self.0)
+    }
+}
+
+/// The members of a JSON object in source order, or in the order they were inserted.
+///
+/// A repeated key keeps the position of its first member. Objects compare equal when they have
+/// the same members, whatever their order. Like [`Array`], objects can only be taken apart with
+/// [`Object::into_map`].
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Object(pub IndexMap<String, JsonValueType>);
+
+impl Object {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn into_map(mut self) -> IndexMap<String, JsonValueType> {
+        std::mem::take(&mut self.0)
+    }
+
+    /// Removes the member with `key` and keeps the order of the others.
+    pub fn remove(&mut self, key: &str) -> Option<JsonValueType> {
+        self.0.shift_remove(key)
+    }
+
+    /// Moves the member with `key` to position `to`, or to the end if `to` is past it, and shifts
+    /// the members in between. Returns false if there is no member with `key`.
+    pub fn move_key(&mut self, key: &str, to: usize) -> bool {
+        let Some(from) = self.0.get_index_of(key) else {
+            return false;
+        };
+        self.0.move_index(from, to.min(self.0.len() - 1));
+        true
+    }
+
+    /// Sorts the members by their keys.
+    pub fn sort_keys(&mut self) {
+        self.0.sort_keys();
+    }
+
+    /// Sorts the members with `compare`, which is given the key and value of two members.
+    pub fn sort_by(&mut self, mut compare: impl FnMut(&str, &JsonValueType, &str, &JsonValueType) -> std::cmp::Ordering) {
+        self.0.sort_by(|k1, v1, k2, v2| compare(k1, v1, k2, v2));
+    }
+}
+
+// Nested containers are moved onto a flat list before they are dropped, dropping them in place
+// would recurse once per level and overflow the stack for deep trees
+fn drop_flat(mut pending: Vec<JsonValueType>) {
+    while let Some(value) = pending.pop() {
+        match value {
+            JsonValueType::JsonTypeArray(mut array) => pending.append(&mut array.0),
+            JsonValueType::JsonTypeObject(mut object) => pending.extend(object.0.drain(..).map(|(_, x)| x)),
+            _ => {}
+        }
+    }
+}
+
+fn is_container(value: &JsonValueType) -> bool {
+    matches!(value, JsonValueType::JsonTypeArray(_) | JsonValueType::JsonTypeObject(_))
+}
+
+impl Drop for Array {
+    fn drop(&mut self) {
+        if self.0.iter().any(is_container) {
+            drop_flat(std::mem::take(&mut self.0));
+        }
     }
 }
-#[derive(Debug, PartialEq, Clone)]
-pub struct Object(pub HashMap<String, JsonValueType>);
 
-impl std::hash::Hash for Object {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        let ptr = self as *const Object;
-        let addr = ptr as usize;
-        state.write_usize(addr);
+impl Drop for Object {
+    fn drop(&mut self) {
+        if self.0.values().any(is_container) {
+            drop_flat(self.0.drain(..).map(|(_, x)| x).collect());
+        }
     }
 }
 
+/// Parses `content` as a single JSON document, anything but whitespace after the top-level value
+/// is an error.
 pub fn to_object(content: &str) -> Result<value::JsonValueType, Error> {
-    let mut state = state::State::new(content);
-    object_parser::parse_value(&mut state)
-}
\ No newline at end of file
+    to_object_bytes(content.as_bytes())
+}
+
+/// Parses UTF-8 encoded `content` like [`to_object`], without converting it to a `str` first.
+/// Strings are checked for valid UTF-8 as they are read.
+pub fn to_object_bytes(content: &[u8]) -> Result<value::JsonValueType, Error> {
+    object_parser::parse_document(&mut state::State::new(content))
+}
+
+/// Parses `content` like [`to_object`], with the behaviour set by `options`.
+pub fn to_object_with(content: &str, options: &options::ParseOptions) -> Result<value::JsonValueType, Error> {
+    object_parser::parse_document(&mut state::State::new(content.as_bytes()).with_options(options))
+}
+
+/// Parses `content` like [`to_object`] into a read-only value that borrows its strings from
+/// `content`, use [`value_ref::JsonValueRef::into_owned`] to get an editable copy.
+pub fn to_object_ref(content: &str) -> Result<value_ref::JsonValueRef<'_>, Error> {
+    object_parser::parse_document(&mut state::State::new(content.as_bytes()))
+}
+
+/// Parses `content` like [`to_object`] and records where every value and key is in `content`.
+pub fn to_object_with_spans(content: &str) -> Result<(value::JsonValueType, span::Spans), Error> {
+    let mut state = state::State::new(content.as_bytes()).with_spans();
+    let value = object_parser::parse_document(&mut state)?;
+    Ok((value, state.take_spans()))
+}
+
+/// Parses `content` like [`to_object_with`] and keeps its comments, see [`comments::CommentMap`].
+pub fn to_object_with_comments(content: &str, options: &options::ParseOptions) -> Result<(value::JsonValueType, comments::CommentMap), Error> {
+    let mut state = state::State::new(content.as_bytes()).with_spans().with_comments().with_options(options);
+    let value = object_parser::parse_document(&mut state)?;
+    let spans = state.take_spans();
+    Ok((value, state.take_comments(&spans)))
+}
+
+/// Parses `content` as a sequence of concatenated JSON documents, e.g. `{"a" : 1} {"a" : 2}`.
+/// Documents may be separated by whitespace, an empty input yields no documents.
+pub fn to_objects(content: &str) -> Result<Vec<value::JsonValueType>, Error> {
+    let mut state = state::State::new(content.as_bytes());
+    let mut result = Vec::new();
+    while !state.skip_whitespace() {
+        result.push(object_parser::parse_value(&mut state)?);
+    }
+    Ok(result)
+}
+
+/// Reads `content` as [JSON Lines](https://jsonlines.org), one document per line, see
+/// [`lines::Lines`].
+pub fn lines(content: &str) -> lines::Lines<'_> {
+    lines::Lines::new(content)
+}
+
+/// The result of a recovering parse.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Recovered {
+    /// The best-effort value, `None` if the input holds no value at all.
+    pub value: Option<value::JsonValueType>,
+
+    /// Every problem that was found, in the order it was found in.
+    pub diagnostics: Vec<Diagnostic>,
+
+    /// Where the values of the recovered tree are in the source.
+    pub spans: span::Spans,
+
+    /// The comments of the source, if the dialect has any.
+    pub comments: comments::CommentMap,
+}
+
+/// Parses `content` like [`to_object`], but keeps going after errors.
+///
+/// Local errors are repaired where possible: missing commas and colons are assumed, unclosed
+/// strings and containers are closed, stray characters are skipped and values that can not be
+/// read are replaced by `null`. Every repair is reported as a [`Diagnostic`], and the spans of the
+/// values are recorded as in [`to_object_with_spans`].
+pub fn to_object_recovering(content: &str) -> Recovered {
+    to_object_recovering_with(content, &options::ParseOptions::default())
+}
+
+/// Parses `content` like [`to_object_recovering`], with the behaviour set by `options`.
+pub fn to_object_recovering_with(content: &str, options: &options::ParseOptions) -> Recovered {
+    let state = state::State::recovering(content.as_bytes()).with_spans().with_comments().with_options(options);
+    recover_document(state)
+}
+
+/// Parses `content` like [`to_object_recovering_with`] and reports how far it has got to
+/// `progress`, e.g. to show it while parsing on another thread. Fails with
+/// [`ErrorKind::Cancelled`] if the parse is cancelled through `progress`.
+pub fn to_object_recovering_with_progress(content: &str, options: &options::ParseOptions, progress: &progress::Progress) -> Result<Recovered, Error> {
+    let state = state::State::recovering(content.as_bytes()).with_spans().with_comments().with_options(options).with_progress(progress);
+    let recovered = recover_document(state);
+    if progress.is_cancelled() {
+        return Err(Error::new(ErrorKind::Cancelled, content.as_bytes(), progress.done()));
+    }
+    progress.set_done(content.len());
+    Ok(recovered)
+}
+
+fn recover_document(mut state: state::State) -> Recovered {
+    let mut value = None;
+    if state.skip_whitespace() {
+        let _ = state.recover(state.error(ErrorKind::EndOfInput).with_expected(&["value"]));
+    } else {
+        // Errors are all collected by the state while recovering
+        value = object_parser::parse_value(&mut state).ok();
+        if !state.skip_whitespace() {
+            let _ = state.recover(state.error(ErrorKind::TrailingCharacters).with_expected(&["end of input"]));
+        }
+    }
+    let spans = state.take_spans();
+    let comments = state.take_comments(&spans);
+    Recovered { value, diagnostics: state.take_diagnostics(), spans, comments }
+}
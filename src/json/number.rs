@@ -0,0 +1,238 @@
+use std::fmt;
+
+/// A JSON number.
+///
+/// Integers are kept as `u64`/`i64` so they round-trip without loss, everything else is stored
+/// as an `f64`. Numbers read from a document also remember their original spelling when the
+/// `f64` alone would not reproduce it (e.g. `1.50`, `1e3` or integers too large for 64 bits),
+/// so that writing an untouched document back out does not change its numeric data.
+#[derive(Debug, Clone)]
+pub struct Number {
+    value: N,
+    lexeme: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum N {
+    // Always non-negative
+    PosInt(u64),
+    // Always negative
+    NegInt(i64),
+    Float(f64),
+}
+
+impl Number {
+    /// Builds a number from a lexeme that has already been validated against the JSON number
+    /// grammar.
+    pub(crate) fn from_lexeme(lexeme: String) -> Number {
+        let is_integer = !lexeme.contains(['.', 'e', 'E']);
+        if is_integer {
+            if let Ok(val) = lexeme.parse::<u64>() {
+                return Number { value: N::PosInt(val), lexeme: None };
+            }
+            // "-0" is not representable as an integer, it falls through to the float case
+            if let Some(val) = lexeme.parse::<i64>().ok().filter(|x| *x < 0) {
+                return Number { value: N::NegInt(val), lexeme: None };
+            }
+        }
+
+        // The grammar has already been checked, so parsing can at worst overflow to infinity
+        let value = lexeme.parse::<f64>().unwrap_or(f64::NAN);
+        let number = Number { value: N::Float(value), lexeme: None };
+        if number.to_string() == lexeme {
+            number
+        } else {
+            Number { lexeme: Some(lexeme), ..number }
+        }
+    }
+
+    /// Keeps `lexeme` as the spelling of the number, for JSON5 numbers such as `0x1F` or `Infinity`
+    /// that have no JSON spelling.
+    pub(crate) fn with_lexeme(self, lexeme: String) -> Number {
+        Number { lexeme: Some(lexeme), ..self }
+    }
+
+    /// The spelling of the number in the source document, if it differs from the canonical one.
+    pub fn lexeme(&self) -> Option<&str> {
+        self.lexeme.as_deref()
+    }
+
+    /// The number in JSON syntax, which is its original spelling unless that is a JSON5 one such
+    /// as `0x1F` or `.5`. `None` for infinity and NaN, which JSON can not represent.
+    pub fn to_json(&self) -> Option<String> {
+        match &self.lexeme {
+            Some(lexeme) if is_json(lexeme) => Some(lexeme.clone()),
+            _ if !self.as_f64().is_finite() => None,
+            _ => Some(Number { value: self.value, lexeme: None }.to_string()),
+        }
+    }
+
+    pub fn is_i64(&self) -> bool {
+        self.as_i64().is_some()
+    }
+
+    pub fn is_u64(&self) -> bool {
+        matches!(self.value, N::PosInt(_))
+    }
+
+    pub fn is_f64(&self) -> bool {
+        matches!(self.value, N::Float(_))
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self.value {
+            N::PosInt(val) => i64::try_from(val).ok(),
+            N::NegInt(val) => Some(val),
+            N::Float(_) => None
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match self.value {
+            N::PosInt(val) => Some(val),
+            _ => None
+        }
+    }
+
+    /// The value as a float, this may lose precision for large integers.
+    pub fn as_f64(&self) -> f64 {
+        match self.value {
+            N::PosInt(val) => val as f64,
+            N::NegInt(val) => val as f64,
+            N::Float(val) => val
+        }
+    }
+}
+
+/// Numbers compare by value, the original spelling is not taken into account.
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.value, other.value) {
+            (N::PosInt(a), N::PosInt(b)) => a == b,
+            (N::NegInt(a), N::NegInt(b)) => a == b,
+            (N::PosInt(_), N::NegInt(_)) | (N::NegInt(_), N::PosInt(_)) => false,
+            _ => self.as_f64() == other.as_f64()
+        }
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(lexeme) = &self.lexeme {
+            return f.write_str(lexeme);
+        }
+        match self.value {
+            N::PosInt(val) => write!(f, "{}", val),
+            N::NegInt(val) => write!(f, "{}", val),
+            // Debug keeps the fraction on whole numbers and switches to exponents for
+            // very large and very small values
+            N::Float(val) => write!(f, "{:?}", val)
+        }
+    }
+}
+
+impl From<u64> for Number {
+    fn from(value: u64) -> Self {
+        Number { value: N::PosInt(value), lexeme: None }
+    }
+}
+
+impl From<i64> for Number {
+    fn from(value: i64) -> Self {
+        match u64::try_from(value) {
+            Ok(val) => Number::from(val),
+            Err(_) => Number { value: N::NegInt(value), lexeme: None }
+        }
+    }
+}
+
+impl From<u32> for Number {
+    fn from(value: u32) -> Self {
+        Number::from(value as u64)
+    }
+}
+
+impl From<i32> for Number {
+    fn from(value: i32) -> Self {
+        Number::from(value as i64)
+    }
+}
+
+impl From<usize> for Number {
+    fn from(value: usize) -> Self {
+        Number::from(value as u64)
+    }
+}
+
+impl From<f64> for Number {
+    fn from(value: f64) -> Self {
+        Number { value: N::Float(value), lexeme: None }
+    }
+}
+
+// Whether `lexeme` follows the JSON number grammar
+fn is_json(lexeme: &str) -> bool {
+    let digits = |x: &str| x.bytes().take_while(u8::is_ascii_digit).count();
+    let rest = lexeme.strip_prefix('-').unwrap_or(lexeme);
+    let count = digits(rest);
+    if count == 0 || (count > 1 && rest.starts_with('0')) {
+        return false;
+    }
+    let mut rest = &rest[count..];
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let count = digits(fraction);
+        if count == 0 {
+            return false;
+        }
+        rest = &fraction[count..];
+    }
+    if let Some(exponent) = rest.strip_prefix(['e', 'E']) {
+        let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+        let count = digits(exponent);
+        if count == 0 {
+            return false;
+        }
+        rest = &exponent[count..];
+    }
+    rest.is_empty()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_number_from_lexeme() {
+        assert_eq!(Number::from_lexeme("9007199254740993".to_string()).as_u64(), Some(9007199254740993));
+        assert_eq!(Number::from_lexeme("-9223372036854775808".to_string()).as_i64(), Some(i64::MIN));
+        assert!(Number::from_lexeme("-0".to_string()).is_f64());
+        assert!(Number::from_lexeme("1.0".to_string()).lexeme().is_none());
+        assert_eq!(Number::from_lexeme("1.50".to_string()).lexeme(), Some("1.50"));
+    }
+
+    #[test]
+    fn test_number_display() {
+        assert_eq!(Number::from(2.0).to_string(), "2.0");
+        assert_eq!(Number::from(-3).to_string(), "-3");
+        assert_eq!(Number::from_lexeme("1e3".to_string()).to_string(), "1e3");
+        assert_eq!(Number::from_lexeme("-0".to_string()).to_string(), "-0");
+        assert_eq!(Number::from_lexeme("18446744073709551616".to_string()).to_string(), "18446744073709551616");
+    }
+
+    #[test]
+    fn test_number_to_json() {
+        assert_eq!(Number::from_lexeme("1.50".to_string()).to_json().as_deref(), Some("1.50"));
+        assert_eq!(Number::from_lexeme("1e400".to_string()).to_json().as_deref(), Some("1e400"));
+        assert_eq!(Number::from(31).with_lexeme("0x1F".to_string()).to_json().as_deref(), Some("31"));
+        assert_eq!(Number::from(0.5).with_lexeme("+.5".to_string()).to_json().as_deref(), Some("0.5"));
+        assert_eq!(Number::from(f64::INFINITY).with_lexeme("Infinity".to_string()).to_json(), None);
+        assert_eq!(Number::from(f64::NAN).to_json(), None);
+    }
+
+    #[test]
+    fn test_number_eq() {
+        assert_eq!(Number::from(1), Number::from(1.0));
+        assert_eq!(Number::from_lexeme("1.50".to_string()), Number::from(1.5));
+        assert_ne!(Number::from(-1), Number::from(1));
+    }
+}
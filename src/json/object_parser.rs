@@ -1,155 +1,818 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
-use super::error::Error;
-use super::error::Error::{EndOfLine};
+use indexmap::IndexMap;
+use super::error::{Error, ErrorKind, Severity};
+use super::error::ErrorKind::{ControlCharacter, ArrayTooLong, DepthLimitExceeded, DuplicateKey, EndOfInput, ExpectedKey, InvalidEscape, InvalidNumber, InvalidUtf8, MissingColon, MissingComma, StringTooLong, TooManyMembers, TrailingCharacters, UnexpectedCharacter, UnterminatedString};
 use super::{Array, Object};
+use super::number::Number;
 use super::value::JsonValueType;
 use super::value::JsonValueType::{JsonTypeArray, JsonTypeBool, JsonTypeNull, JsonTypeNumber, JsonTypeObject, JsonTypeString};
+use super::value_ref::JsonValueRef;
+use super::options::{Dialect, DuplicateKeys};
+use super::path::PathSegment;
 use super::state::State;
 
+// The value types the parser can build, borrowing strings from the input where the type allows
+pub(crate) trait ParsedValue<'a>: Sized {
+    type Map: Default;
 
+    fn null() -> Self;
+    fn bool(value: bool) -> Self;
+    fn number(value: Number) -> Self;
+    fn string(value: Cow<'a, str>) -> Self;
+    fn array(values: Vec<Self>) -> Self;
+    fn object(map: Self::Map) -> Self;
+    fn insert(map: &mut Self::Map, key: Cow<'a, str>, value: Self);
+    fn get_mut<'m>(map: &'m mut Self::Map, key: &str) -> Option<&'m mut Self>;
+    // Only called on arrays
+    fn push(array: &mut Self, value: Self);
+}
+
+impl<'a> ParsedValue<'a> for JsonValueType {
+    type Map = IndexMap<String, JsonValueType>;
+
+    fn null() -> Self { JsonTypeNull }
+    fn bool(value: bool) -> Self { JsonTypeBool(value) }
+    fn number(value: Number) -> Self { JsonTypeNumber(value) }
+    fn string(value: Cow<'a, str>) -> Self { JsonTypeString(value.into_owned()) }
+    fn array(values: Vec<Self>) -> Self { JsonTypeArray(Array(values)) }
+    fn object(map: Self::Map) -> Self { JsonTypeObject(Object(map)) }
+    fn insert(map: &mut Self::Map, key: Cow<'a, str>, value: Self) {
+        map.insert(key.into_owned(), value);
+    }
+    fn get_mut<'m>(map: &'m mut Self::Map, key: &str) -> Option<&'m mut Self> {
+        map.get_mut(key)
+    }
+    fn push(array: &mut Self, value: Self) {
+        if let JsonTypeArray(Array(values)) = array {
+            values.push(value);
+        }
+    }
+}
+
+impl<'a> ParsedValue<'a> for JsonValueRef<'a> {
+    type Map = Vec<(Cow<'a, str>, JsonValueRef<'a>)>;
+
+    fn null() -> Self { JsonValueRef::JsonTypeNull }
+    fn bool(value: bool) -> Self { JsonValueRef::JsonTypeBool(value) }
+    fn number(value: Number) -> Self { JsonValueRef::JsonTypeNumber(value) }
+    fn string(value: Cow<'a, str>) -> Self { JsonValueRef::JsonTypeString(value) }
+    fn array(values: Vec<Self>) -> Self { JsonValueRef::JsonTypeArray(values) }
+    fn object(map: Self::Map) -> Self { JsonValueRef::JsonTypeObject(map) }
+    fn insert(map: &mut Self::Map, key: Cow<'a, str>, value: Self) {
+        map.push((key, value));
+    }
+    fn get_mut<'m>(map: &'m mut Self::Map, key: &str) -> Option<&'m mut Self> {
+        map.iter_mut().rev().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+    fn push(array: &mut Self, value: Self) {
+        if let JsonValueRef::JsonTypeArray(values) = array {
+            values.push(value);
+        }
+    }
+}
+
+// Parses a single value that has to be followed by the end of the input
+pub(crate) fn parse_document<'a, V: ParsedValue<'a>>(state : &mut State<'a>) -> Result<V, Error> {
+    state.check_input_size()?;
+    let value = parse_value(state)?;
+    if !state.skip_whitespace() {
+        return Err(state.error(TrailingCharacters).with_expected(&["end of input"]));
+    }
+    Ok(value)
+}
+
+// A container that is still being read
+enum Frame<'a, V: ParsedValue<'a>> {
+    Array {
+        start: usize,
+        values: Vec<V>,
+    },
+    Object {
+        start: usize,
+        map: V::Map,
+        // Only needed to handle or report repeated keys
+        seen: Option<HashMap<Cow<'a, str>, FirstKey>>,
+        // The member whose value is being read
+        key: Cow<'a, str>,
+        repeated: bool,
+        members: usize,
+    },
+}
 
-pub(crate) fn parse_value(state : &mut State) -> Result<JsonValueType, Error> {
+// Where a key was first seen in an object
+struct FirstKey {
+    start: usize,
+    end: usize,
+    // Whether the value has been turned into an array for `DuplicateKeys::KeepAll`
+    merged: bool,
+}
+
+// What comes next inside the innermost container
+enum Step {
+    Value,
+    Close,
+}
+
+// Containers are kept on an explicit stack instead of recursing, so deeply nested input can not
+// overflow the call stack
+pub(crate) fn parse_value<'a, V: ParsedValue<'a>>(state : &mut State<'a>) -> Result<V, Error> {
+    let mut stack: Vec<Frame<'a, V>> = Vec::new();
+    let mut step = Step::Value;
+    loop {
+        let value = match step {
+            Step::Value => match state.check_progress().and_then(|_| start_value(state, stack.len()))? {
+                Some(value) => value,
+                None => {
+                    let start = state.cursor() - 1;
+                    let opened = if state.slice(start, start + 1) == b"[" {
+                        Frame::Array { start, values: Vec::new() }
+                    } else {
+                        Frame::Object { start, map: V::Map::default(), seen: None, key: Cow::Borrowed(""), repeated: false, members: 0 }
+                    };
+                    stack.push(opened);
+                    step = begin_container(state, stack.last_mut().unwrap())?;
+                    continue;
+                }
+            },
+            Step::Close => {
+                let (value, start) = match stack.pop().unwrap() {
+                    Frame::Array { start, values } => (V::array(values), start),
+                    Frame::Object { start, map, .. } => (V::object(map), start),
+                };
+                state.record_value(start);
+                value
+            }
+        };
+        match stack.last_mut() {
+            None => return Ok(value),
+            Some(Frame::Array { values, .. }) => {
+                state.pop_path();
+                values.push(value);
+                step = after_element(state, values)?;
+            }
+            Some(frame) => {
+                state.pop_path();
+                step = after_member(state, frame, value)?;
+            }
+        }
+    }
+}
+
+// Reads a scalar value, or the bracket of a container in which case `None` is returned
+fn start_value<'a, V: ParsedValue<'a>>(state : &mut State<'a>, depth: usize) -> Result<Option<V>, Error> {
     if state.skip_whitespace() {
-        return Err(EndOfLine)
+        state.recover(state.error(EndOfInput).with_expected(&["value"]))?;
+        return Ok(Some(V::null()));
     }
 
-    return match state.peek() {
-        Some(&'t') | Some(&'f') => {
-            if state.read_literal("true") {Ok(JsonTypeBool(true))}
-            else if state.read_literal("false") {Ok(JsonTypeBool(false))}
-            else { Err(state.error()) }
-        },
-        Some(&'n') => {
-            if state.read_literal("null") {Ok(JsonTypeNull)}
-            else { Err(state.error())}
+    let start = state.cursor();
+    let json5 = state.dialect() == Dialect::Json5;
+    let value = loop {
+        match state.peek() {
+            Some(b't') => break parse_literal(state, "true", V::bool(true))?,
+            Some(b'f') => break parse_literal(state, "false", V::bool(false))?,
+            Some(b'n') => break parse_literal(state, "null", V::null())?,
+            Some(b'[') | Some(b'{') => {
+                if depth >= state.options().max_depth {
+                    state.recover(state.error(DepthLimitExceeded))?;
+                    skip_container(state);
+                    break V::null();
+                }
+                state.take();
+                return Ok(None);
+            }
+            Some(b'"') => {
+                state.take();
+                break V::string(parse_string(state)?);
+            },
+            Some(b'-') => break parse_number(state)?,
+            Some(x) if x.is_ascii_digit() => break parse_number(state)?,
+            Some(b'\'') if json5 => {
+                state.take();
+                break V::string(parse_string(state)?);
+            }
+            Some(b'+' | b'.' | b'I' | b'N') if json5 => break parse_number(state)?,
+            _ => {
+                state.recover(state.error(UnexpectedCharacter).with_expected(&["value"]))?;
+                // Skip stray characters, brackets and separators are left to the enclosing container
+                state.skip_while(|x| !is_whitespace(x) && !is_structural(x) && !starts_value(x));
+                if !state.peek().is_some_and(starts_value) {
+                    break V::null();
+                }
+            }
         }
-        Some(&'[') => {
-            state.take();
-            parse_array(state)
-        },
-        Some(&'"') => {
-            state.take();
-            parse_string(state)
-        },
-        Some(&'{') => {
-            state.take();
-            parse_object(state)
+    };
+    state.record_value(start);
+    Ok(Some(value))
+}
+
+// Skips a container that is nested too deeply without building it, brackets in strings are ignored
+fn skip_container(state: &mut State) {
+    let mut depth = 0;
+    let mut in_string = false;
+    while let Some(c) = state.take() {
+        match c {
+            b'\\' if in_string => { state.take(); }
+            b'"' => in_string = !in_string,
+            b'[' | b'{' if !in_string => depth += 1,
+            b']' | b'}' if !in_string => {
+                depth -= 1;
+                if depth == 0 {
+                    return;
+                }
+            }
+            _ => {}
         }
-        _ => parse_number(state)
     }
 }
 
-fn parse_object(state: &mut State) -> Result<JsonValueType, Error> {
+fn starts_value(c: u8) -> bool {
+    matches!(c, b'"' | b'{' | b'[' | b'-' | b't' | b'f' | b'n') || c.is_ascii_digit()
+}
+
+fn is_structural(c: u8) -> bool {
+    matches!(c, b'{' | b'}' | b'[' | b']' | b',' | b':')
+}
 
-    let mut result = HashMap::new();
+fn is_whitespace(c: u8) -> bool {
+    matches!(c, b' ' | b'\t' | b'\n' | b'\r')
+}
 
-    if state.read_char('}') {
-        return Ok(JsonTypeObject(Object(result)))
+fn parse_literal<'a, V: ParsedValue<'a>>(state: &mut State<'a>, literal: &'static str, value: V) -> Result<V, Error> {
+    if state.read_literal(literal) {
+        Ok(value)
+    } else {
+        // read_literal stops at the first character that does not match
+        let kind = if state.at_end() { EndOfInput } else { UnexpectedCharacter };
+        state.recover(state.error(kind).with_expected(&[literal]))?;
+        state.skip_while(|x| x.is_ascii_alphanumeric());
+        Ok(V::null())
     }
+}
 
-    loop {
-        let key = parse_value(state);
-        let key_string: String;
-        match key {
-            Ok(JsonTypeString(val)) => key_string = val,
-            _ => return Err(state.error())
+// Called right after the opening bracket
+fn begin_container<'a, V: ParsedValue<'a>>(state: &mut State<'a>, frame: &mut Frame<'a, V>) -> Result<Step, Error> {
+    match frame {
+        Frame::Array { values, .. } => {
+            if state.read_char(b']') {
+                return Ok(Step::Close);
+            }
+            begin_element(state, values)
+        }
+        Frame::Object { .. } => {
+            if state.read_char(b'}') {
+                return Ok(Step::Close);
+            }
+            let policy = state.options().duplicate_keys;
+            if let Frame::Object { seen, .. } = frame {
+                if policy != DuplicateKeys::KeepLast || state.is_recovering() {
+                    *seen = Some(HashMap::new());
+                }
+            }
+            begin_member(state, frame)
         }
-        if !state.read_char(':') { return Err(state.error())}
-        let value = parse_value(state);
-        if value.is_err() {return Err(state.error());}
-        result.insert(key_string, value.unwrap());
-        if state.read_char('}') {break;}
-        else if state.read_char(',') {continue;}
-        else {return Err(state.error())}
     }
+}
 
-    Ok(JsonTypeObject(Object(result)))
+fn begin_member<'a, V: ParsedValue<'a>>(state: &mut State<'a>, frame: &mut Frame<'a, V>) -> Result<Step, Error> {
+    let Frame::Object { seen, key, repeated, members, .. } = frame else { unreachable!() };
+    if state.dialect().allows_trailing_commas() && state.read_char(b'}') {
+        return Ok(Step::Close);
+    }
+    loop {
+        state.skip_whitespace();
+        let key_start = state.cursor();
+        let key_string = match parse_key(state)? {
+            Some(key) => key,
+            None => {
+                // Nothing usable as a key, e.g. after a trailing comma
+                if state.read_char(b',') { continue; }
+                state.read_char(b'}');
+                return Ok(Step::Close);
+            }
+        };
+        if *members >= state.options().max_object_members {
+            state.recover(state.error_at(TooManyMembers, key_start))?;
+        }
+        let key_end = state.cursor();
+        if !state.read_char(b':') {
+            state.recover(structure_error(state, MissingColon, &["':'"]))?;
+        }
 
+        state.push_path(|| PathSegment::Key(key_string.to_string()));
+        *repeated = false;
+        if let Some(seen) = seen.as_mut() {
+            if let Some(first) = seen.get(&key_string) {
+                *repeated = true;
+                let severity = if state.options().duplicate_keys == DuplicateKeys::Error { Severity::Error } else { Severity::Warning };
+                state.report(state.error_at(DuplicateKey, key_start), severity, Some((first.start, first.end)))?;
+            } else {
+                seen.insert(key_string.clone(), FirstKey { start: key_start, end: key_end, merged: false });
+            }
+        }
+        // The spans of a member that is left out would point away from the one in the value
+        if *repeated && state.options().duplicate_keys == DuplicateKeys::KeepFirst {
+            state.ignore_spans();
+        } else {
+            state.record_key(key_start, key_end);
+        }
+        *key = key_string;
+        *members += 1;
+        return Ok(Step::Value);
+    }
 }
 
-fn parse_array(state: &mut State) -> Result<JsonValueType, Error> {
+// Called with the value of the member that `begin_member` started
+fn after_member<'a, V: ParsedValue<'a>>(state: &mut State<'a>, frame: &mut Frame<'a, V>, value: V) -> Result<Step, Error> {
+    let Frame::Object { map, seen, key, repeated, .. } = frame else { unreachable!() };
+    let key_string = std::mem::take(key);
+    match state.options().duplicate_keys {
+        DuplicateKeys::KeepFirst if *repeated => {}
+        DuplicateKeys::KeepAll if *repeated => {
+            let first = seen.as_mut().and_then(|x| x.get_mut(&key_string));
+            if let (Some(first), Some(existing)) = (first, V::get_mut(map, &key_string)) {
+                if !first.merged {
+                    let previous = std::mem::replace(existing, V::null());
+                    *existing = V::array(vec![previous]);
+                    first.merged = true;
+                }
+                V::push(existing, value);
+            }
+        }
+        _ => V::insert(map, key_string, value),
+    }
 
-    let mut vec = Vec::new();
+    let mut reported = false;
+    loop {
+        if state.read_char(b'}') {return Ok(Step::Close);}
+        else if state.read_char(b',') {return begin_member(state, frame);}
 
-    // Empty Vector
-    if state.peek().is_some_and(|x| x == &']' ) {
+        if !reported {
+            state.recover(structure_error(state, MissingComma, &["','", "'}'"]))?;
+            reported = true;
+        }
+        // Unclosed object or mismatched bracket, the latter is left to the enclosing array
+        if state.at_end() || state.peek() == Some(b']') {return Ok(Step::Close);}
+        // A missing comma between members
+        if state.peek() == Some(b'"') {return begin_member(state, frame);}
         state.take();
-        return Ok(JsonTypeArray(Array(vec)));
     }
+}
+
+// Returns `None` if no key could be recovered
+pub(crate) fn parse_key<'a>(state: &mut State<'a>) -> Result<Option<Cow<'a, str>>, Error> {
+    if state.read_char(b'"') {
+        return parse_string(state).map(Some);
+    }
+    if state.dialect() == Dialect::Json5 {
+        if state.read_char(b'\'') {
+            return parse_string(state).map(Some);
+        }
+        // Identifier keys as in `{name : 1}`, non-ASCII letters are taken as they are
+        if state.peek().is_some_and(|x| x.is_ascii_alphabetic() || x == b'_' || x == b'$' || x >= 0x80) {
+            let start = state.cursor();
+            state.skip_while(|x| x.is_ascii_alphanumeric() || x == b'_' || x == b'$' || x >= 0x80);
+            return match std::str::from_utf8(state.slice(start, state.cursor())) {
+                Ok(key) => Ok(Some(Cow::Borrowed(key))),
+                Err(error) => Err(state.error_at(InvalidUtf8, start + error.valid_up_to())),
+            };
+        }
+    }
+
+    state.recover(structure_error(state, ExpectedKey, &["string"]))?;
+    // Use a bare word as the key, as in `{name : 1}`
+    let start = state.cursor();
+    state.skip_while(|x| !is_whitespace(x) && !is_structural(x) && x != b'"');
+    let key = String::from_utf8_lossy(state.slice(start, state.cursor()));
+    // Without even a bare word, only `: value` still makes a member, with an empty key
+    if key.is_empty() && (state.skip_whitespace() || state.peek() != Some(b':')) {
+        return Ok(None);
+    }
+    Ok(Some(key))
+}
 
+// Error for a missing structural token, the end of the input takes precedence over `kind`
+fn structure_error(state: &State, kind: ErrorKind, expected: &[&'static str]) -> Error {
+    let kind = if state.at_end() { EndOfInput } else { kind };
+    state.error(kind).with_expected(expected)
+}
+
+fn begin_element<'a, V: ParsedValue<'a>>(state: &mut State<'a>, values: &[V]) -> Result<Step, Error> {
+    if state.dialect().allows_trailing_commas() && state.read_char(b']') {
+        return Ok(Step::Close);
+    }
+    if state.skip_whitespace() || state.peek() == Some(b']') {
+        // Unclosed array or trailing comma
+        state.recover(state.error(if state.at_end() { EndOfInput } else { UnexpectedCharacter }).with_expected(&["value"]))?;
+        state.read_char(b']');
+        return Ok(Step::Close);
+    }
+    if values.len() >= state.options().max_array_length {
+        state.recover(state.error(ArrayTooLong))?;
+    }
+    state.push_path(|| PathSegment::Index(values.len()));
+    Ok(Step::Value)
+}
+
+// Called after an element has been added to `values`
+fn after_element<'a, V: ParsedValue<'a>>(state: &mut State<'a>, values: &[V]) -> Result<Step, Error> {
+    let mut reported = false;
     loop {
-        let value = parse_value(state)?;
-        vec.push(value);
-        if state.skip_whitespace() { return Err(EndOfLine) }
+        if state.read_char(b',') {return begin_element(state, values);}
+        else if state.read_char(b']') {return Ok(Step::Close);}
 
-        match state.take() {
-            Some(&',') => continue,
-            Some(&']') => break,
-            _ => return Err(state.error())
+        if !reported {
+            state.recover(structure_error(state, MissingComma, &["','", "']'"]))?;
+            reported = true;
         }
+        // Unclosed array or mismatched bracket, the latter is left to the enclosing object
+        if state.at_end() || state.peek() == Some(b'}') {return Ok(Step::Close);}
+        // A missing comma between elements
+        if state.peek().is_some_and(starts_value) {return begin_element(state, values);}
+        state.take();
     }
+}
 
-    return Ok(JsonTypeArray(Array(vec)));
+fn parse_number<'a, V: ParsedValue<'a>>(state: &mut State<'a>) -> Result<V, Error> {
+    match read_number(state) {
+        Ok(value) => Ok(V::number(value)),
+        Err(error) => {
+            state.recover(error)?;
+            state.skip_while(|x| x.is_ascii_alphanumeric() || matches!(x, b'.' | b'+' | b'-'));
+            Ok(V::null())
+        }
+    }
 }
 
-fn parse_number(state: &mut State) -> Result<JsonValueType, Error> {
-    let valid = vec![
-        '0','1','2','3','4','5','6','7','8','9','-','e','E','.'
-    ];
+fn read_number(state: &mut State) -> Result<Number, Error> {
+    if state.dialect() == Dialect::Json5 {
+        return read_json5_number(state);
+    }
+    let start = state.cursor();
+
+    if state.peek() == Some(b'-') {
+        state.take();
+    }
 
-    let mut value = String::new();
+    // Integer part, a leading zero may not be followed by further digits
+    match state.peek() {
+        Some(b'0') => {
+            state.take();
+            if state.peek().is_some_and(|x| x.is_ascii_digit()) {
+                return Err(state.error(InvalidNumber).with_expected(&["'.'", "'e'", "end of number"]));
+            }
+        }
+        _ => take_digits(state)?
+    }
 
-    loop {
-        match state.peek() {
-            None => break,
+    // Fraction
+    if state.peek() == Some(b'.') {
+        state.take();
+        take_digits(state)?;
+    }
 
-            x => if !valid.contains(x.unwrap()) {
-                break;
+    // Exponent
+    if state.peek().is_some_and(|x| x == b'e' || x == b'E') {
+        state.take();
+        if state.peek().is_some_and(|x| x == b'+' || x == b'-') {
+            state.take();
+        }
+        take_digits(state)?;
+    }
+
+    // The lexeme is plain ASCII
+    let lexeme = String::from_utf8_lossy(state.slice(start, state.cursor())).into_owned();
+    Ok(Number::from_lexeme(lexeme))
+}
+
+// JSON5 also has hexadecimal integers, `Infinity`, `NaN`, a leading `+` and a leading or trailing
+// decimal point
+fn read_json5_number(state: &mut State) -> Result<Number, Error> {
+    let start = state.cursor();
+    let negative = state.peek() == Some(b'-');
+    if state.peek().is_some_and(|x| x == b'+' || x == b'-') {
+        state.take();
+    }
+    let lexeme = |state: &State| String::from_utf8_lossy(state.slice(start, state.cursor())).into_owned();
+
+    for (name, value) in [("Infinity", f64::INFINITY), ("NaN", f64::NAN)] {
+        if state.peek() == Some(name.as_bytes()[0]) {
+            if !state.read_literal(name) {
+                let kind = if state.at_end() { EndOfInput } else { InvalidNumber };
+                return Err(state.error(kind).with_expected(&[name]));
+            }
+            let value = if negative { -value } else { value };
+            return Ok(Number::from(value).with_lexeme(lexeme(state)));
+        }
+    }
+
+    let rest = state.slice(state.cursor(), (state.cursor() + 2).min(state.len()));
+    if rest.eq_ignore_ascii_case(b"0x") {
+        state.skip_to(state.cursor() + 2);
+        let digits = state.cursor();
+        state.skip_while(|x| x.is_ascii_hexdigit());
+        if digits == state.cursor() {
+            return Err(state.error(InvalidNumber).with_expected(&["hex digit"]));
+        }
+        let text = String::from_utf8_lossy(state.slice(digits, state.cursor())).into_owned();
+        let number = u64::from_str_radix(&text, 16).ok().and_then(|x| {
+            if !negative {
+                Some(Number::from(x))
+            } else if x == 0 {
+                Some(Number::from(-0.0))
             } else {
-                value.push(*x.unwrap());
-                state.take();
+                0i64.checked_sub_unsigned(x).map(Number::from)
             }
+        });
+        return match number {
+            Some(number) => Ok(number.with_lexeme(lexeme(state))),
+            None => Err(state.error_at(InvalidNumber, start)),
+        };
+    }
+
+    // Integer part, a leading zero may not be followed by further digits
+    let mut digits = false;
+    match state.peek() {
+        Some(b'0') => {
+            state.take();
+            digits = true;
+            if state.peek().is_some_and(|x| x.is_ascii_digit()) {
+                return Err(state.error(InvalidNumber).with_expected(&["'.'", "'e'", "end of number"]));
+            }
+        }
+        Some(x) if x.is_ascii_digit() => {
+            state.skip_while(|x| x.is_ascii_digit());
+            digits = true;
         }
+        _ => {}
     }
 
-    let num = value.parse::<f64>();
-    if num.is_err() {
-        Err(state.error())
+    // Fraction, the digits on one side of the point may be left out
+    if state.peek() == Some(b'.') {
+        state.take();
+        if digits {
+            state.skip_while(|x| x.is_ascii_digit());
+        } else {
+            take_digits(state)?;
+        }
+    } else if !digits {
+        return Err(state.error(InvalidNumber).with_expected(&["digit"]));
+    }
+
+    // Exponent
+    if state.peek().is_some_and(|x| x == b'e' || x == b'E') {
+        state.take();
+        if state.peek().is_some_and(|x| x == b'+' || x == b'-') {
+            state.take();
+        }
+        take_digits(state)?;
+    }
+
+    let text = lexeme(state);
+    let number = Number::from_lexeme(text.trim_start_matches('+').to_string());
+    if number.to_string() == text {
+        Ok(number)
     } else {
-        Ok(JsonTypeNumber(num.unwrap()))
+        Ok(number.with_lexeme(text))
     }
+}
 
+// Takes a run of one or more decimal digits
+fn take_digits(state: &mut State) -> Result<(), Error> {
+    if !state.peek().is_some_and(|x| x.is_ascii_digit()) {
+        return Err(state.error(InvalidNumber).with_expected(&["digit"]));
+    }
+    state.skip_while(|x| x.is_ascii_digit());
+    Ok(())
 }
 
-fn parse_string(state: &mut State) -> Result<JsonValueType, Error> {
+// The length limit is checked as the string grows so that an overlong string is never held in
+// full, when recovering it is cut off at the limit
+pub(crate) fn parse_string<'a>(state: &mut State<'a>) -> Result<Cow<'a, str>, Error> {
+    // Position of the opening quote, which has already been taken
+    let start = state.cursor() - 1;
+    // JSON5 strings may also be in single quotes
+    let quote = state.slice(start, start + 1)[0];
+    let limit = state.options().max_string_length;
+
+    let first = state.cursor();
+    state.skip_while(|x| is_plain_string_byte(x, quote));
+    if state.peek() == Some(quote) {
+        // Without escapes the string can be borrowed from the input
+        if let Ok(text) = std::str::from_utf8(state.slice(first, state.cursor())) {
+            state.take();
+            if text.len() > limit {
+                state.recover(state.error_at(StringTooLong, start))?;
+                return Ok(Cow::Borrowed(&text[..floor_char_boundary(text, limit)]));
+            }
+            return Ok(Cow::Borrowed(text));
+        }
+    }
+
     let mut result = String::new();
-    let mut slash = false;
+    let mut too_long = false;
+    push_run(state, &mut result, first, start, &mut too_long)?;
     loop {
-        match state.take() {
-            Some(&'\\') => {
-                if slash {
-                    result.push('\\');
-                    slash = false;
-                } else {
-                    slash = true;
-                }
-            },
-            Some(&'"') => {
-                if slash {
-                    result.push('"');
-                    slash = false;
-                } else {
+        let pushed = match state.take() {
+            Some(x) if x == quote => break,
+            Some(b'\\') => match parse_escape(state) {
+                Ok(escaped) => escaped,
+                Err(error) if error.kind() == EndOfInput => {
+                    state.recover(state.error_at(UnterminatedString, start).with_expected(&["'\"'"]))?;
                     break;
                 }
+                // The broken escape is left out of the recovered string
+                Err(error) => {
+                    state.recover(error)?;
+                    None
+                }
+            },
+            // Strings can not span lines, when recovering a line break ends the string
+            Some(b'\n') if state.is_recovering() => {
+                state.recover(state.error_at(UnterminatedString, start).with_expected(&["'\"'"]))?;
+                break;
             }
+            // Control characters have to be escaped inside of strings
             Some(x) => {
-                slash = false;
-                result.push(*x);
-            },
-            None => return Err(EndOfLine)
+                state.recover(state.error_at(ControlCharacter, state.cursor() - 1))?;
+                Some(x as char)
+            }
+            None => {
+                state.recover(state.error_at(UnterminatedString, start).with_expected(&["'\"'"]))?;
+                break;
+            }
+        };
+        // `None` is a JSON5 line continuation, which stands for nothing
+        if let Some(c) = pushed.filter(|_| !too_long) {
+            if result.len() + c.len_utf8() > limit {
+                state.recover(state.error_at(StringTooLong, start))?;
+                too_long = true;
+            } else {
+                result.push(c);
+            }
+        }
+
+        // Copy runs of plain characters in one go
+        let run_start = state.cursor();
+        state.skip_while(|x| is_plain_string_byte(x, quote));
+        push_run(state, &mut result, run_start, start, &mut too_long)?;
+    }
+    Ok(Cow::Owned(result))
+}
+
+// Appends the run of plain characters from `run_start` up to the cursor, unless the string would
+// get longer than the limit
+fn push_run(state: &mut State, result: &mut String, run_start: usize, start: usize, too_long: &mut bool) -> Result<(), Error> {
+    if *too_long {
+        return Ok(());
+    }
+    let limit = state.options().max_string_length;
+    if result.len() + state.cursor() - run_start > limit {
+        state.recover(state.error_at(StringTooLong, start))?;
+        *too_long = true;
+        // Whatever still fits is kept
+        let end = run_start + (limit - result.len());
+        let text = String::from_utf8_lossy(state.slice(run_start, end)).into_owned();
+        result.push_str(&text[..floor_char_boundary(&text, limit - result.len())]);
+        return Ok(());
+    }
+    push_utf8(state, result, run_start)
+}
+
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    let mut index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+fn is_plain_string_byte(c: u8, quote: u8) -> bool {
+    c != quote && c != b'\\' && c >= 0x20
+}
+
+// Appends the bytes from `start` up to the cursor, which have to be valid UTF-8
+fn push_utf8(state: &mut State, result: &mut String, start: usize) -> Result<(), Error> {
+    let bytes = state.slice(start, state.cursor());
+    if bytes.is_empty() {
+        return Ok(());
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(text) => result.push_str(text),
+        Err(error) => {
+            state.recover(state.error_at(InvalidUtf8, start + error.valid_up_to()))?;
+            result.push_str(&String::from_utf8_lossy(bytes));
         }
     }
-    return Ok(JsonTypeString(result));
+    Ok(())
 }
 
+// Decodes the escape sequence following a backslash, the backslash has already been taken.
+// Returns `None` for escapes that stand for nothing, i.e. JSON5 line continuations.
+fn parse_escape(state: &mut State) -> Result<Option<char>, Error> {
+    match state.take() {
+        Some(b'"') => Ok(Some('"')),
+        Some(b'\\') => Ok(Some('\\')),
+        Some(b'/') => Ok(Some('/')),
+        Some(b'b') => Ok(Some('\u{8}')),
+        Some(b'f') => Ok(Some('\u{c}')),
+        Some(b'n') => Ok(Some('\n')),
+        Some(b'r') => Ok(Some('\r')),
+        Some(b't') => Ok(Some('\t')),
+        Some(b'u') => parse_unicode_escape(state).map(Some),
+        Some(_) if state.dialect() == Dialect::Json5 => {
+            state.cursor_back();
+            parse_json5_escape(state)
+        }
+        Some(_) => Err(state.error_at(InvalidEscape, state.cursor() - 1)
+            .with_expected(&["'\"'", "'\\'", "'/'", "'b'", "'f'", "'n'", "'r'", "'t'", "'u'"])),
+        None => Err(state.error(EndOfInput))
+    }
+}
 
+// The escapes JSON5 adds to those of JSON, any other character stands for itself
+fn parse_json5_escape(state: &mut State) -> Result<Option<char>, Error> {
+    let position = state.cursor();
+    match state.take() {
+        Some(b'\'') => Ok(Some('\'')),
+        Some(b'v') => Ok(Some('\u{b}')),
+        Some(b'0') if !state.peek().is_some_and(|x| x.is_ascii_digit()) => Ok(Some('\0')),
+        Some(b'x') => {
+            let mut code = 0;
+            for _ in 0..2 {
+                code = code * 16 + parse_hex_digit(state)?;
+            }
+            Ok(char::from_u32(code))
+        }
+        Some(b'\r') => {
+            state.read_byte(b'\n');
+            Ok(None)
+        }
+        Some(b'\n') => Ok(None),
+        Some(b'1'..=b'9') | Some(b'0') => Err(state.error_at(InvalidEscape, position)),
+        Some(x) if x < 0x80 => Ok(Some(x as char)),
+        // A non-ASCII character, U+2028 and U+2029 are line continuations
+        Some(_) => {
+            let rest = state.slice(position, (position + 4).min(state.len()));
+            let text = String::from_utf8_lossy(rest);
+            let c = text.chars().next().unwrap_or(char::REPLACEMENT_CHARACTER);
+            if c == char::REPLACEMENT_CHARACTER {
+                return Err(state.error_at(InvalidUtf8, position));
+            }
+            state.skip_to(position + c.len_utf8());
+            Ok(Some(c).filter(|x| !matches!(x, '\u{2028}' | '\u{2029}')))
+        }
+        None => Err(state.error(EndOfInput))
+    }
+}
+
+// Decodes the XXXX of a \uXXXX escape, combining UTF-16 surrogate pairs into one character
+fn parse_unicode_escape(state: &mut State) -> Result<char, Error> {
+    // Position of the backslash that started the escape
+    let start = state.cursor() - 2;
+    let high = parse_hex4(state)?;
+
+    let code = match high {
+        0xD800..=0xDBFF => {
+            let low_start = state.cursor();
+            // Whatever follows instead is left to the string, it may be the closing quote
+            let rest = state.slice(low_start, (low_start + 2).min(state.len()));
+            if rest != b"\\u" {
+                let kind = if b"\\u".starts_with(rest) { EndOfInput } else { InvalidEscape };
+                return Err(state.error_at(kind, low_start).with_expected(&["low surrogate"]));
+            }
+            state.skip_to(low_start + 2);
+            let low = parse_hex4(state)?;
+            if !(0xDC00..=0xDFFF).contains(&low) {
+                return Err(state.error_at(InvalidEscape, low_start).with_expected(&["low surrogate"]));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        }
+        // A low surrogate without a preceding high surrogate
+        0xDC00..=0xDFFF => return Err(state.error_at(InvalidEscape, start)),
+        _ => high
+    };
+
+    char::from_u32(code).ok_or_else(|| state.error_at(InvalidEscape, start))
+}
+
+fn parse_hex4(state: &mut State) -> Result<u32, Error> {
+    let mut result = 0;
+    for _ in 0..4 {
+        result = result * 16 + parse_hex_digit(state)?;
+    }
+    Ok(result)
+}
+
+// A byte that is not a hex digit is not taken, so a closing quote still ends the string
+fn parse_hex_digit(state: &mut State) -> Result<u32, Error> {
+    match state.peek().and_then(|x| (x as char).to_digit(16)) {
+        Some(digit) => {
+            state.take();
+            Ok(digit)
+        }
+        None if state.at_end() => Err(state.error(EndOfInput)),
+        None => Err(state.error(InvalidEscape).with_expected(&["hex digit"]))
+    }
+}
@@ -0,0 +1,132 @@
+/// What to do when an object has the same key more than once.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum DuplicateKeys {
+    /// Fail with [`super::error::ErrorKind::DuplicateKey`].
+    Error,
+
+    /// Keep the value of the first member and ignore the others.
+    KeepFirst,
+
+    /// Keep the value of the last member, as most JSON parsers do.
+    #[default]
+    KeepLast,
+
+    /// Keep every value, in source order, in an array under the key.
+    KeepAll,
+}
+
+/// The syntax the input is written in.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Dialect {
+    /// Strict JSON as in RFC 8259.
+    #[default]
+    Json,
+
+    /// [JSON5](https://spec.json5.org): comments, trailing commas, identifier keys, single-quoted
+    /// strings, hexadecimal numbers, `Infinity` and `NaN`, and leading `+` or `.` on numbers.
+    Json5,
+
+    /// JSON with comments as used by VS Code settings and `tsconfig.json`, which also allows
+    /// trailing commas.
+    Jsonc,
+}
+
+impl Dialect {
+    pub fn allows_comments(self) -> bool {
+        self != Dialect::Json
+    }
+
+    pub fn allows_trailing_commas(self) -> bool {
+        self != Dialect::Json
+    }
+}
+
+/// Settings for [`super::to_object_with`], the defaults parse strict JSON like [`super::to_object`].
+///
+/// Only the nesting depth is limited by default, every limit that is hit is reported with its own
+/// [`super::error::ErrorKind`].
+///
+/// ```
+/// use json_editor::json::options::ParseOptions;
+///
+/// let options = ParseOptions::new()
+///     .with_max_input_size(1 << 20)
+///     .with_max_string_length(1024);
+/// assert!(json_editor::json::to_object_with("[\"short\"]", &options).is_ok());
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct ParseOptions {
+    pub dialect: Dialect,
+
+    pub duplicate_keys: DuplicateKeys,
+
+    /// How many arrays and objects may be nested inside each other, the top-level container
+    /// counts as one.
+    pub max_depth: usize,
+
+    /// Length of the whole input in bytes, or of each record for [`super::lines::Lines`].
+    pub max_input_size: usize,
+
+    /// Length of a string or key in bytes, after escapes have been replaced. Parsing with
+    /// recovery cuts longer strings off at the limit.
+    pub max_string_length: usize,
+
+    pub max_object_members: usize,
+
+    pub max_array_length: usize,
+}
+
+impl ParseOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    pub fn with_duplicate_keys(mut self, policy: DuplicateKeys) -> Self {
+        self.duplicate_keys = policy;
+        self
+    }
+
+    pub fn with_max_depth(mut self, limit: usize) -> Self {
+        self.max_depth = limit;
+        self
+    }
+
+    pub fn with_max_input_size(mut self, limit: usize) -> Self {
+        self.max_input_size = limit;
+        self
+    }
+
+    pub fn with_max_string_length(mut self, limit: usize) -> Self {
+        self.max_string_length = limit;
+        self
+    }
+
+    pub fn with_max_object_members(mut self, limit: usize) -> Self {
+        self.max_object_members = limit;
+        self
+    }
+
+    pub fn with_max_array_length(mut self, limit: usize) -> Self {
+        self.max_array_length = limit;
+        self
+    }
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            dialect: Dialect::default(),
+            duplicate_keys: DuplicateKeys::default(),
+            max_depth: 128,
+            max_input_size: usize::MAX,
+            max_string_length: usize::MAX,
+            max_object_members: usize::MAX,
+            max_array_length: usize::MAX,
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use std::fmt;
+
+/// One step from a container to one of its children.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// The location of a value inside of a document, starting from the top-level value.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Default)]
+pub struct Path(pub Vec<PathSegment>);
+
+impl Path {
+    pub fn root() -> Self {
+        Path(Vec::new())
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns a new path one level below this one.
+    pub fn join(&self, segment: PathSegment) -> Path {
+        let mut result = self.clone();
+        result.0.push(segment);
+        result
+    }
+}
+
+/// Formats the path as `$.key[0]`, keys that are not plain identifiers are quoted as `$["a b"]`.
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("$")?;
+        for segment in &self.0 {
+            match segment {
+                PathSegment::Index(index) => write!(f, "[{}]", index)?,
+                PathSegment::Key(key) => {
+                    let plain = key.chars().next().is_some_and(|x| x.is_alphabetic() || x == '_')
+                        && key.chars().all(|x| x.is_alphanumeric() || x == '_');
+                    if plain {
+                        write!(f, ".{}", key)?
+                    } else {
+                        write!(f, "[{:?}]", key)?
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_path_display() {
+        assert_eq!(Path::root().to_string(), "$");
+        let path = Path::root()
+            .join(PathSegment::Key("servers".to_string()))
+            .join(PathSegment::Index(2))
+            .join(PathSegment::Key("host name".to_string()));
+        assert_eq!(path.to_string(), "$.servers[2][\"host name\"]");
+    }
+}
@@ -0,0 +1,55 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// How far a parse on another thread has got, and a way to stop it.
+///
+/// Share it with the parsing thread in an `Arc`, the parser updates it about every
+/// [`REPORT_INTERVAL`] bytes and stops with [`super::error::ErrorKind::Cancelled`] at the next
+/// update after [`Progress::cancel`] has been called.
+#[derive(Debug, Default)]
+pub struct Progress {
+    done: AtomicUsize,
+    total: AtomicUsize,
+    cancelled: AtomicBool,
+}
+
+/// Number of bytes between two updates of a [`Progress`].
+pub const REPORT_INTERVAL: usize = 1 << 16;
+
+impl Progress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bytes that have been handled so far.
+    pub fn done(&self) -> usize {
+        self.done.load(Ordering::Relaxed)
+    }
+
+    /// Bytes that will be handled in total, 0 while that is not known.
+    pub fn total(&self) -> usize {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    /// How much is done between 0 and 1, `None` while the total is not known.
+    pub fn fraction(&self) -> Option<f32> {
+        let total = self.total();
+        (total > 0).then(|| (self.done() as f64 / total as f64).min(1.0) as f32)
+    }
+
+    pub fn set_done(&self, done: usize) {
+        self.done.store(done, Ordering::Relaxed);
+    }
+
+    pub fn set_total(&self, total: usize) {
+        self.total.store(total, Ordering::Relaxed);
+    }
+
+    /// Asks the work to stop, it may take until the next update to notice.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,237 @@
+use std::borrow::Cow;
+use super::error::Error;
+use super::error::ErrorKind::{EndOfInput, ExpectedKey, MissingColon, MissingComma, TrailingCharacters};
+use super::object_parser;
+use super::path::{Path, PathSegment};
+use super::state::State;
+use super::value_ref::JsonValueRef;
+
+/// One step through a document, as produced by [`Reader`].
+#[derive(Debug, PartialEq, Clone)]
+pub enum Event<'a> {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+
+    /// An object key, the next event starts its value.
+    Key(Cow<'a, str>),
+
+    /// A scalar value, never an array or an object.
+    Value(JsonValueRef<'a>),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Expect {
+    Value,
+    FirstKeyOrEnd,
+    FirstValueOrEnd,
+    Key,
+    CommaOrEnd,
+    EndOfInput,
+    Done,
+}
+
+enum Frame<'a> {
+    Object(Option<Cow<'a, str>>),
+    Array(usize),
+}
+
+/// Reads a document as a stream of [`Event`]s without building the tree.
+///
+/// ```
+/// use json_editor::json::reader::{Event, Reader};
+///
+/// let mut reader = Reader::new(r#"{"id" : 7, "tags" : ["a"]}"#);
+/// while let Some(event) = reader.next() {
+///     if event.unwrap() == Event::Key("tags".into()) {
+///         break;
+///     }
+/// }
+/// assert_eq!(reader.path().to_string(), "$.tags");
+/// ```
+pub struct Reader<'a> {
+    state: State<'a>,
+    stack: Vec<Frame<'a>>,
+    expect: Expect,
+    offset: usize,
+    // Number of stack frames that make up the path of the last event
+    path_len: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(content: &'a str) -> Self {
+        Self::from_bytes(content.as_bytes())
+    }
+
+    /// Reads UTF-8 encoded `content`, see [`super::to_object_bytes`].
+    pub fn from_bytes(content: &'a [u8]) -> Self {
+        Self {
+            state: State::new(content),
+            stack: Vec::new(),
+            expect: Expect::Value,
+            offset: 0,
+            path_len: 0,
+        }
+    }
+
+    /// Byte offset at which the last event starts.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Number of containers that are still open after the last event.
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Location of the value the last event belongs to, for a key that is the key's value.
+    pub fn path(&self) -> Path {
+        Path(self.stack[..self.path_len].iter().filter_map(|frame| match frame {
+            Frame::Object(key) => key.as_ref().map(|x| PathSegment::Key(x.to_string())),
+            Frame::Array(index) => Some(PathSegment::Index(*index)),
+        }).collect())
+    }
+
+    /// Skips the next value entirely, e.g. the value after a [`Event::Key`] that is not needed.
+    pub fn skip_value(&mut self) -> Result<(), Error> {
+        let mut depth = 0;
+        loop {
+            match self.next().transpose()? {
+                Some(Event::StartObject) | Some(Event::StartArray) => depth += 1,
+                Some(Event::EndObject) | Some(Event::EndArray) => depth -= 1,
+                Some(Event::Key(_)) => continue,
+                Some(Event::Value(_)) => {},
+                None => return Ok(())
+            }
+            if depth <= 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    fn read_event(&mut self) -> Result<Option<Event<'a>>, Error> {
+        loop {
+            match self.expect {
+                Expect::Value => {
+                    if self.state.skip_whitespace() {
+                        return Err(self.state.error(EndOfInput).with_expected(&["value"]));
+                    }
+                    self.offset = self.state.cursor();
+                    self.path_len = self.stack.len();
+                    return match self.state.peek() {
+                        Some(b'{') => {
+                            self.state.take();
+                            self.stack.push(Frame::Object(None));
+                            self.expect = Expect::FirstKeyOrEnd;
+                            Ok(Some(Event::StartObject))
+                        }
+                        Some(b'[') => {
+                            self.state.take();
+                            self.stack.push(Frame::Array(0));
+                            self.expect = Expect::FirstValueOrEnd;
+                            Ok(Some(Event::StartArray))
+                        }
+                        _ => {
+                            let value = object_parser::parse_value(&mut self.state)?;
+                            self.end_value();
+                            Ok(Some(Event::Value(value)))
+                        }
+                    };
+                }
+                Expect::FirstKeyOrEnd => {
+                    if self.state.read_char(b'}') {
+                        return Ok(Some(self.end_container(Event::EndObject)));
+                    }
+                    self.expect = Expect::Key;
+                }
+                Expect::FirstValueOrEnd => {
+                    if self.state.read_char(b']') {
+                        return Ok(Some(self.end_container(Event::EndArray)));
+                    }
+                    self.expect = Expect::Value;
+                }
+                Expect::Key => {
+                    if !self.state.read_char(b'"') {
+                        return Err(self.structure_error(ExpectedKey, &["string"]));
+                    }
+                    self.offset = self.state.cursor() - 1;
+                    let key = object_parser::parse_string(&mut self.state)?;
+                    if !self.state.read_char(b':') {
+                        return Err(self.structure_error(MissingColon, &["':'"]));
+                    }
+                    if let Some(Frame::Object(current)) = self.stack.last_mut() {
+                        *current = Some(key.clone());
+                    }
+                    self.path_len = self.stack.len();
+                    self.expect = Expect::Value;
+                    return Ok(Some(Event::Key(key)));
+                }
+                Expect::CommaOrEnd => {
+                    match self.stack.last_mut() {
+                        Some(Frame::Array(index)) => {
+                            if self.state.read_char(b',') {
+                                *index += 1;
+                                self.expect = Expect::Value;
+                            } else if self.state.read_char(b']') {
+                                return Ok(Some(self.end_container(Event::EndArray)));
+                            } else {
+                                return Err(self.structure_error(MissingComma, &["','", "']'"]));
+                            }
+                        }
+                        _ => {
+                            if self.state.read_char(b',') {
+                                self.expect = Expect::Key;
+                            } else if self.state.read_char(b'}') {
+                                return Ok(Some(self.end_container(Event::EndObject)));
+                            } else {
+                                return Err(self.structure_error(MissingComma, &["','", "'}'"]));
+                            }
+                        }
+                    }
+                }
+                Expect::EndOfInput => {
+                    self.expect = Expect::Done;
+                    if !self.state.skip_whitespace() {
+                        return Err(self.state.error(TrailingCharacters).with_expected(&["end of input"]));
+                    }
+                    return Ok(None);
+                }
+                Expect::Done => return Ok(None),
+            }
+        }
+    }
+
+    // The closing bracket has just been read
+    fn end_container(&mut self, event: Event<'a>) -> Event<'a> {
+        self.offset = self.state.cursor() - 1;
+        self.stack.pop();
+        self.path_len = self.stack.len();
+        self.end_value();
+        event
+    }
+
+    fn end_value(&mut self) {
+        self.expect = if self.stack.is_empty() { Expect::EndOfInput } else { Expect::CommaOrEnd };
+    }
+
+    fn structure_error(&self, kind: super::error::ErrorKind, expected: &[&'static str]) -> Error {
+        let kind = if self.state.at_end() { EndOfInput } else { kind };
+        self.state.error(kind).with_expected(expected)
+    }
+}
+
+impl<'a> Iterator for Reader<'a> {
+    type Item = Result<Event<'a>, Error>;
+
+    /// Returns the next event, after an error the reader is finished.
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.read_event() {
+            Ok(event) => event.map(Ok),
+            Err(error) => {
+                self.expect = Expect::Done;
+                Some(Err(error))
+            }
+        }
+    }
+}
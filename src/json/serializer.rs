@@ -0,0 +1,440 @@
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::collections::HashSet;
+use super::comments::{Comment, CommentMap, Comments};
+use super::path::{Path, PathSegment};
+use super::value::JsonValueType;
+use super::{Array, Object};
+
+// Output is collected in a string and handed to the writer in chunks of about this size
+const BUFFER_SIZE: usize = 1 << 16;
+
+/// How the output is laid out.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Style {
+    /// Every member and element on its own line, indented by its depth.
+    #[default]
+    Pretty,
+
+    /// No whitespace at all.
+    Compact,
+}
+
+/// What one level of indentation is made of in [`Style::Pretty`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Indent {
+    Spaces(usize),
+    Tab,
+}
+
+impl Default for Indent {
+    fn default() -> Self {
+        Indent::Spaces(4)
+    }
+}
+
+/// Settings for [`to_writer`] and [`to_string`], the defaults write indented JSON with 4 spaces
+/// and the object members in their order.
+///
+/// ```
+/// use json_editor::json::serializer::{self, Indent, SerializeOptions};
+///
+/// let value = json_editor::json::to_object("{\"b\" : [1, 2], \"a\" : \"é\"}").unwrap();
+/// let options = SerializeOptions::new().with_indent(Indent::Spaces(2)).with_sort_keys(true).with_ascii_only(true);
+/// assert_eq!(serializer::to_string(&value, &options).unwrap(), "{\n  \"a\": \"\\u00e9\",\n  \"b\": [\n    1,\n    2\n  ]\n}");
+/// assert_eq!(serializer::to_string(&value, &SerializeOptions::compact()).unwrap(), "{\"b\":[1,2],\"a\":\"é\"}");
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct SerializeOptions {
+    pub style: Style,
+
+    pub indent: Indent,
+
+    /// In [`Style::Pretty`], arrays and objects that fit on a line of at most this many bytes,
+    /// indentation included, are written on that line.
+    pub line_width: Option<usize>,
+
+    /// Write object members in the order of their keys instead of their own order.
+    pub sort_keys: bool,
+
+    /// Escape every character outside of ASCII as `\uXXXX`.
+    pub ascii_only: bool,
+}
+
+impl SerializeOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn compact() -> Self {
+        Self::new().with_style(Style::Compact)
+    }
+
+    pub fn with_style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn with_indent(mut self, indent: Indent) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    pub fn with_line_width(mut self, width: usize) -> Self {
+        self.line_width = Some(width);
+        self
+    }
+
+    pub fn with_sort_keys(mut self, sort_keys: bool) -> Self {
+        self.sort_keys = sort_keys;
+        self
+    }
+
+    pub fn with_ascii_only(mut self, ascii_only: bool) -> Self {
+        self.ascii_only = ascii_only;
+        self
+    }
+}
+
+/// Writes `value` as JSON to `writer`, which is written to in large chunks so it does not need
+/// to be buffered.
+///
+/// Fails with [`io::ErrorKind::InvalidData`] for infinite and NaN numbers, which JSON has no
+/// spelling for. Part of the value may have been written by then.
+pub fn to_writer<W: Write>(writer: W, value: &JsonValueType, options: &SerializeOptions) -> io::Result<()> {
+    Serializer::new(writer, options, None, false).write(value)
+}
+
+/// Writes `value` as JSON into a string, see [`to_writer`].
+pub fn to_string(value: &JsonValueType, options: &SerializeOptions) -> io::Result<String> {
+    write_to_string(value, options, None, false)
+}
+
+/// Writes `value` as JSON into a string with the comments from `comments` put back next to the
+/// values they belong to, e.g. to save a JSONC document after editing it.
+///
+/// Arrays and objects with comments inside are never put on one line. The compact style has no
+/// lines to put comments on and leaves them out.
+pub fn to_string_with_comments(value: &JsonValueType, comments: &CommentMap, options: &SerializeOptions) -> io::Result<String> {
+    write_to_string(value, options, Some(comments), false)
+}
+
+// Writing that can not fail, infinite and NaN numbers are written as `null` like JavaScript's
+// `JSON.stringify` does
+pub(crate) fn to_string_lossy(value: &JsonValueType, options: &SerializeOptions) -> String {
+    write_to_string(value, options, None, true).expect("lossy writing does not fail")
+}
+
+fn write_to_string(value: &JsonValueType, options: &SerializeOptions, comments: Option<&CommentMap>, lossy: bool) -> io::Result<String> {
+    let mut out = Vec::new();
+    Serializer::new(&mut out, options, comments, lossy).write(value)?;
+    Ok(String::from_utf8(out).expect("serializer writes UTF-8"))
+}
+
+// The layout of `JsonValueType`'s `Display`: one line with spaces around the colons and after
+// the commas, infinite and NaN numbers are written as `null`
+pub(crate) fn write_inline(out: &mut String, value: &JsonValueType) {
+    // The arrays and objects being written, with the children that are still to come
+    let mut stack: Vec<(Children, char)> = Vec::new();
+    let mut next = Some(value);
+    loop {
+        if let Some(value) = next.take() {
+            match brackets(value) {
+                Some((open, close)) => {
+                    out.push(open);
+                    stack.push((children(value, false).into_iter().enumerate(), close));
+                }
+                None => write_scalar(out, value, false, true).expect("lossy writing does not fail"),
+            }
+        }
+        let Some((children, close)) = stack.last_mut() else { return };
+        match children.next() {
+            Some((i, (key, child))) => {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                if let Some(key) = key {
+                    write_string(out, key, false);
+                    out.push_str(" : ");
+                }
+                next = Some(child);
+            }
+            None => {
+                out.push(*close);
+                stack.pop();
+            }
+        }
+    }
+}
+
+// The remaining children of an array or object, with their index
+type Children<'v> = std::iter::Enumerate<std::vec::IntoIter<(Option<&'v str>, &'v JsonValueType)>>;
+
+// An array or object that `Serializer::value` has started on more than one line
+struct Open<'v, 'a> {
+    children: Children<'v>,
+    count: usize,
+    close: char,
+    level: usize,
+    inner: &'a [Comment],
+    // The child being written and its comments
+    current: Option<(usize, Option<&'a Comments>)>,
+}
+
+struct Serializer<'a, W: Write> {
+    writer: W,
+    out: String,
+    options: &'a SerializeOptions,
+    lossy: bool,
+    comments: Option<&'a CommentMap>,
+    // Paths of the arrays and objects with comments inside
+    commented: HashSet<Path>,
+    // Of the value being written, only kept up to date when there are comments
+    path: Path,
+}
+
+impl<'a, W: Write> Serializer<'a, W> {
+    fn new(writer: W, options: &'a SerializeOptions, comments: Option<&'a CommentMap>, lossy: bool) -> Self {
+        // Comments need lines of their own
+        let comments = comments.filter(|_| options.style == Style::Pretty);
+        let mut commented = HashSet::new();
+        for (path, x) in comments.iter().flat_map(|x| x.0.iter()).filter(|(_, x)| !x.is_empty()) {
+            let depth = if x.inner.is_empty() { path.0.len() } else { path.0.len() + 1 };
+            for depth in 0..depth {
+                commented.insert(Path(path.0[..depth].to_vec()));
+            }
+        }
+        Serializer { writer, out: String::new(), options, lossy, comments, commented, path: Path::root() }
+    }
+
+    fn write(mut self, value: &JsonValueType) -> io::Result<()> {
+        let comments = self.comments_here();
+        for comment in comments.map_or(&[][..], |x| &x.leading[..]) {
+            self.out.push_str(&comment.text);
+            self.new_line(0);
+        }
+        self.value(value, 0, 0)?;
+        self.write_trailing(comments);
+        self.flush()
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.write_all(self.out.as_bytes())?;
+        self.out.clear();
+        Ok(())
+    }
+
+    // The comments of the value being written
+    fn comments_here(&self) -> Option<&'a Comments> {
+        self.comments.and_then(|x| x.get(&self.path))
+    }
+
+    fn write_trailing(&mut self, comments: Option<&Comments>) {
+        for comment in comments.map_or(&[][..], |x| &x.trailing[..]) {
+            self.out.push(' ');
+            self.out.push_str(&comment.text);
+        }
+    }
+
+    // `column` is where the value starts on its line
+    fn value(&mut self, value: &JsonValueType, level: usize, column: usize) -> io::Result<()> {
+        let mut stack: Vec<Open> = Vec::new();
+        let mut next = Some((value, level, column));
+        loop {
+            if let Some((value, level, column)) = next.take() {
+                if let Some(open) = self.open(value, level, column)? {
+                    stack.push(open);
+                }
+            }
+            let Some(open) = stack.last_mut() else { return Ok(()) };
+            if let Some((i, comments)) = open.current.take() {
+                if i + 1 < open.count {
+                    self.out.push(',');
+                }
+                self.write_trailing(comments);
+                if self.comments.is_some() {
+                    self.path.0.pop();
+                }
+                if self.out.len() >= BUFFER_SIZE {
+                    self.flush()?;
+                }
+            }
+
+            match open.children.next() {
+                Some((i, (key, child))) => {
+                    if self.comments.is_some() {
+                        self.path.0.push(key.map_or(PathSegment::Index(i), |x| PathSegment::Key(x.to_string())));
+                    }
+                    let comments = self.comments_here();
+                    let mut start = self.new_line(open.level + 1);
+                    for comment in comments.map_or(&[][..], |x| &x.leading[..]) {
+                        self.out.push_str(&comment.text);
+                        start = self.new_line(open.level + 1);
+                    }
+                    if let Some(key) = key {
+                        write_string(&mut self.out, key, self.options.ascii_only);
+                        self.out.push(':');
+                        if self.options.style == Style::Pretty {
+                            self.out.push(' ');
+                        }
+                    }
+                    open.current = Some((i, comments));
+                    next = Some((child, open.level + 1, self.out.len() - start));
+                }
+                None => {
+                    for comment in open.inner {
+                        self.new_line(open.level + 1);
+                        self.out.push_str(&comment.text);
+                    }
+                    self.new_line(open.level);
+                    self.out.push(open.close);
+                    stack.pop();
+                }
+            }
+        }
+    }
+
+    // Writes a scalar, or an array or object that fits on one line, and returns `None`.
+    // Otherwise writes the opening bracket and returns what is needed to write the rest.
+    fn open<'v>(&mut self, value: &'v JsonValueType, level: usize, column: usize) -> io::Result<Option<Open<'v, 'a>>> {
+        let Some((open, close)) = brackets(value) else {
+            write_scalar(&mut self.out, value, self.options.ascii_only, self.lossy)?;
+            return Ok(None);
+        };
+        let children = children(value, self.options.sort_keys);
+        let inner = self.comments_here().map_or(&[][..], |x| &x.inner[..]);
+        if children.is_empty() && inner.is_empty() {
+            self.out.push(open);
+            self.out.push(close);
+            return Ok(None);
+        }
+        if self.options.style == Style::Pretty && !self.commented.contains(&self.path) {
+            if let Some(width) = self.options.line_width {
+                let mut line = String::new();
+                if self.single_line(&mut line, value, width.saturating_sub(column))? {
+                    self.out.push_str(&line);
+                    return Ok(None);
+                }
+            }
+        }
+        self.out.push(open);
+        Ok(Some(Open { count: children.len(), children: children.into_iter().enumerate(), close, level, inner, current: None }))
+    }
+
+    // Starts a line in the pretty style and returns where it starts in `out`
+    fn new_line(&mut self, level: usize) -> usize {
+        if self.options.style == Style::Compact {
+            return self.out.len();
+        }
+        self.out.push('\n');
+        let start = self.out.len();
+        for _ in 0..level {
+            match self.options.indent {
+                Indent::Spaces(count) => self.out.extend(std::iter::repeat_n(' ', count)),
+                Indent::Tab => self.out.push('\t'),
+            }
+        }
+        start
+    }
+
+    // Writes `value` to `line` with spaces after the separators, returns false as soon as it
+    // takes more than `width` bytes
+    fn single_line(&self, line: &mut String, value: &JsonValueType, width: usize) -> io::Result<bool> {
+        let mut stack: Vec<(Children, char)> = Vec::new();
+        let mut next = Some(value);
+        loop {
+            if let Some(value) = next.take() {
+                match brackets(value) {
+                    Some((open, close)) => {
+                        line.push(open);
+                        stack.push((children(value, self.options.sort_keys).into_iter().enumerate(), close));
+                    }
+                    None => write_scalar(line, value, self.options.ascii_only, self.lossy)?,
+                }
+            }
+            if line.len() > width {
+                return Ok(false);
+            }
+            let Some((children, close)) = stack.last_mut() else { return Ok(true) };
+            match children.next() {
+                Some((i, (key, child))) => {
+                    if i > 0 {
+                        line.push_str(", ");
+                    }
+                    if let Some(key) = key {
+                        write_string(line, key, self.options.ascii_only);
+                        line.push_str(": ");
+                    }
+                    next = Some(child);
+                }
+                None => {
+                    line.push(*close);
+                    stack.pop();
+                }
+            }
+        }
+    }
+}
+
+fn brackets(value: &JsonValueType) -> Option<(char, char)> {
+    match value {
+        JsonValueType::JsonTypeArray(_) => Some(('[', ']')),
+        JsonValueType::JsonTypeObject(_) => Some(('{', '}')),
+        _ => None,
+    }
+}
+
+// The elements of an array or the members of an object with their keys
+fn children(value: &JsonValueType, sort_keys: bool) -> Vec<(Option<&str>, &JsonValueType)> {
+    match value {
+        JsonValueType::JsonTypeArray(Array(values)) => values.iter().map(|x| (None, x)).collect(),
+        JsonValueType::JsonTypeObject(Object(members)) => {
+            let mut children: Vec<_> = members.iter().map(|(key, x)| (Some(key.as_str()), x)).collect();
+            if sort_keys {
+                children.sort_by_key(|x| x.0);
+            }
+            children
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn write_scalar(out: &mut String, value: &JsonValueType, ascii_only: bool, lossy: bool) -> io::Result<()> {
+    match value {
+        JsonValueType::JsonTypeNull => out.push_str("null"),
+        JsonValueType::JsonTypeBool(val) => out.push_str(if *val { "true" } else { "false" }),
+        JsonValueType::JsonTypeNumber(val) => match val.to_json() {
+            Some(text) => out.push_str(&text),
+            None if lossy => out.push_str("null"),
+            None => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{} can not be written as JSON", val))),
+        },
+        JsonValueType::JsonTypeString(val) => write_string(out, val, ascii_only),
+        JsonValueType::JsonTypeArray(_) | JsonValueType::JsonTypeObject(_) => unreachable!("not a scalar"),
+    }
+    Ok(())
+}
+
+/// Writes `value` as a JSON string with quotes and escapes.
+pub(crate) fn write_string(out: &mut String, value: &str, ascii_only: bool) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c if ascii_only && !c.is_ascii() => {
+                // Characters outside of the basic plane take a surrogate pair
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    write!(out, "\\u{:04x}", unit).unwrap();
+                }
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+use super::path::{Path, PathSegment};
+
+/// A range of the source text, with the line and column where it starts.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Span {
+    /// Byte offset of the first byte.
+    pub start: usize,
+
+    /// Byte offset just past the last byte.
+    pub end: usize,
+
+    /// 1-based line of `start`.
+    pub line: usize,
+
+    /// 1-based column of `start`, counted in characters.
+    pub column: usize,
+}
+
+/// Where a value and, for object members, its key came from.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct NodeSpan {
+    pub value: Span,
+    pub key: Option<Span>,
+}
+
+/// Source locations of every value in a document, keyed by the path of the value.
+///
+/// For repeated keys the last member wins, like in the parsed value.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Spans(HashMap<Path, NodeSpan>);
+
+impl Spans {
+    pub fn get(&self, path: &Path) -> Option<&NodeSpan> {
+        self.0.get(path)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Path, &NodeSpan)> {
+        self.0.iter()
+    }
+
+    /// The innermost value whose source contains the byte at `offset`.
+    pub fn find(&self, offset: usize) -> Option<&Path> {
+        self.0.iter()
+            .filter(|(_, span)| span.value.start <= offset && offset < span.value.end)
+            .max_by_key(|(path, _)| path.0.len())
+            .map(|(path, _)| path)
+    }
+
+    pub(crate) fn record_value(&mut self, path: &[PathSegment], start: usize, end: usize) {
+        self.0.entry(Path(path.to_vec())).or_default().value = Span { start, end, line: 0, column: 0 };
+    }
+
+    pub(crate) fn record_key(&mut self, path: &[PathSegment], start: usize, end: usize) {
+        self.0.entry(Path(path.to_vec())).or_default().key = Some(Span { start, end, line: 0, column: 0 });
+    }
+
+    pub(crate) fn insert(&mut self, path: Path, span: NodeSpan) {
+        self.0.insert(path, span);
+    }
+
+    pub(crate) fn get_mut(&mut self, path: &Path) -> Option<&mut NodeSpan> {
+        self.0.get_mut(path)
+    }
+
+    pub(crate) fn remove(&mut self, path: &Path) -> Option<NodeSpan> {
+        self.0.remove(path)
+    }
+
+    // Fills in the lines and columns in a single pass over the source
+    pub(crate) fn locate(&mut self, source: &[u8]) {
+        let mut spans: Vec<&mut Span> = self.0.values_mut()
+            .flat_map(|x| std::iter::once(&mut x.value).chain(x.key.as_mut()))
+            .collect();
+        spans.sort_by_key(|x| x.start);
+
+        let (mut offset, mut line, mut column) = (0, 1, 1);
+        for span in spans {
+            let start = span.start.min(source.len());
+            for byte in &source[offset..start] {
+                if *byte == b'\n' {
+                    line += 1;
+                    column = 1;
+                } else if *byte & 0xC0 != 0x80 {
+                    // Continuation bytes belong to the character before
+                    column += 1;
+                }
+            }
+            offset = start;
+            span.line = line;
+            span.column = column;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_locate() {
+        let mut spans = Spans::default();
+        let key = [PathSegment::Key("é".to_string())];
+        spans.record_value(&[], 0, 14);
+        spans.record_key(&key, 4, 8);
+        spans.record_value(&key, 11, 12);
+        spans.locate("{\n  \"é\" : 1\n}".as_bytes());
+
+        let member = spans.get(&Path(key.to_vec())).unwrap();
+        assert_eq!((member.key.unwrap().line, member.key.unwrap().column), (2, 3));
+        assert_eq!((member.value.line, member.value.column), (2, 9));
+        assert_eq!(spans.find(11), Some(&Path(key.to_vec())));
+        assert_eq!(spans.find(1), Some(&Path::root()));
+    }
+}
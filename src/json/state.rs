@@ -1,25 +1,221 @@
-use super::error::Error;
-use super::error::Error::{Character, EndOfLine};
+use std::cell::Cell;
+use super::error::{Diagnostic, Error, ErrorKind, Position, Severity};
+use super::options::{Dialect, ParseOptions};
+use super::path::{Path, PathSegment};
+use super::comments::CommentMap;
+use super::progress::{Progress, REPORT_INTERVAL};
+use super::span::{Span, Spans};
 
-pub struct State {
-    content: Vec<char>,
-    cursor: usize
+// Scans UTF-8 input byte by byte, all positions are byte offsets into the input
+pub struct State<'a> {
+    content: &'a [u8],
+    cursor: usize,
+    recovering: bool,
+    diagnostics: Vec<Diagnostic>,
+    // Only kept up to date while recovering or recording spans, both need to know where they are
+    path: Vec<PathSegment>,
+    spans: Option<Spans>,
+    // Length of the path below which no spans are recorded, for members that are left out of the value
+    ignored_from: Option<usize>,
+    options: ParseOptions,
+    // Copied from the options, whitespace is skipped too often to look it up every time
+    comments: bool,
+    // Byte ranges of the comments that have been skipped, if they are kept
+    found_comments: Option<Vec<(usize, usize)>>,
+    // Start of a block comment that is not closed, errors at that position are about the comment
+    unclosed_comment: Option<usize>,
+    progress: Option<&'a Progress>,
+    // Cursor position at which the progress is updated next
+    next_report: usize,
+    // Where the last error was located, the next one is counted from there
+    position: Cell<Position>,
 }
 
-impl State {
-    pub fn new(string: &str) -> Self {
+impl<'a> State<'a> {
+    pub fn new(content: &'a [u8]) -> Self {
         Self {
             cursor: 0,
-            content: string.chars().collect(),
+            content,
+            recovering: false,
+            diagnostics: Vec::new(),
+            path: Vec::new(),
+            spans: None,
+            ignored_from: None,
+            options: ParseOptions::default(),
+            comments: false,
+            found_comments: None,
+            unclosed_comment: None,
+            progress: None,
+            next_report: 0,
+            position: Cell::new(Position::START),
         }
     }
 
-    pub fn error(&self) -> Error {
-        if self.at_end() {
-            EndOfLine
-        } else {
-            Character(self.cursor)
+    // A state that collects errors as diagnostics instead of stopping at the first one
+    pub fn recovering(content: &'a [u8]) -> Self {
+        Self {
+            recovering: true,
+            ..Self::new(content)
+        }
+    }
+
+    pub fn with_options(mut self, options: &ParseOptions) -> Self {
+        self.options = options.clone();
+        self.comments = options.dialect.allows_comments();
+        self
+    }
+
+    pub fn dialect(&self) -> Dialect {
+        self.options.dialect
+    }
+
+    pub fn options(&self) -> &ParseOptions {
+        &self.options
+    }
+
+    // The input is checked as a whole before anything is parsed
+    pub fn check_input_size(&self) -> Result<(), Error> {
+        // Measured from the cursor, in case the document does not start the input
+        let limit = self.options.max_input_size;
+        if self.content.len() - self.cursor > limit {
+            return Err(self.error_at(ErrorKind::InputTooLarge, self.cursor + limit));
+        }
+        Ok(())
+    }
+
+    // Returns the error when not recovering, otherwise records it and lets the parser carry on
+    pub fn recover(&mut self, error: Error) -> Result<(), Error> {
+        self.report(error, Severity::Error, None)
+    }
+
+    // Like `recover`, warnings are only recorded while recovering and never stop the parser.
+    // `related` is the byte range of another place the problem involves.
+    pub fn report(&mut self, error: Error, severity: Severity, related: Option<(usize, usize)>) -> Result<(), Error> {
+        if !self.recovering {
+            return match severity {
+                Severity::Error => Err(error),
+                Severity::Warning => Ok(()),
+            };
+        }
+        // Repairs can run into the same problem more than once, e.g. at the end of the input
+        let repeated = self.diagnostics.last()
+            .is_some_and(|x| x.error.offset() == error.offset() && x.error.kind() == error.kind());
+        if !repeated {
+            let related = related.map(|(start, end)| {
+                let Position { line, column, .. } = self.locate(start);
+                Span { start, end, line, column }
+            });
+            self.diagnostics.push(Diagnostic { error, path: Path(self.path.clone()), severity, related });
+        }
+        Ok(())
+    }
+
+    // Also records where every value is, see `record_value` and `record_key`
+    pub fn with_spans(mut self) -> Self {
+        self.spans = Some(Spans::default());
+        self
+    }
+
+    pub fn with_progress(mut self, progress: &'a Progress) -> Self {
+        progress.set_total(self.content.len());
+        self.progress = Some(progress);
+        self
+    }
+
+    // Updates the progress now and then, and stops the parse once it has been cancelled
+    pub fn check_progress(&mut self) -> Result<(), Error> {
+        if let Some(progress) = self.progress.filter(|_| self.cursor >= self.next_report) {
+            progress.set_done(self.cursor);
+            self.next_report = self.cursor + REPORT_INTERVAL;
+            if progress.is_cancelled() {
+                return Err(self.error(ErrorKind::Cancelled));
+            }
+        }
+        Ok(())
+    }
+
+    // Keeps the comments for `take_comments`, spans have to be recorded as well
+    pub fn with_comments(mut self) -> Self {
+        self.found_comments = Some(Vec::new());
+        self
+    }
+
+    pub fn is_recovering(&self) -> bool {
+        self.recovering
+    }
+
+    pub fn push_path(&mut self, segment: impl FnOnce() -> PathSegment) {
+        if self.recovering || self.spans.is_some() {
+            self.path.push(segment());
+        }
+    }
+
+    pub fn pop_path(&mut self) {
+        self.path.pop();
+        if self.ignored_from.is_some_and(|x| self.path.len() < x) {
+            self.ignored_from = None;
+        }
+    }
+
+    // No spans are recorded for the current path and the paths inside of it until it is popped
+    pub fn ignore_spans(&mut self) {
+        if self.ignored_from.is_none() {
+            self.ignored_from = Some(self.path.len());
+        }
+    }
+
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.diagnostics)
+    }
+
+    pub fn record_value(&mut self, start: usize) {
+        if self.ignored_from.is_some() {
+            return;
         }
+        if let Some(spans) = &mut self.spans {
+            spans.record_value(&self.path, start, self.cursor);
+        }
+    }
+
+    // Has to be called after the path of the member has been pushed
+    pub fn record_key(&mut self, start: usize, end: usize) {
+        if self.ignored_from.is_some() {
+            return;
+        }
+        if let Some(spans) = &mut self.spans {
+            spans.record_key(&self.path, start, end);
+        }
+    }
+
+    pub fn take_comments(&mut self, spans: &Spans) -> CommentMap {
+        let found = self.found_comments.take().unwrap_or_default();
+        CommentMap::attach(self.content, &found, spans)
+    }
+
+    pub fn take_spans(&mut self) -> Spans {
+        let mut spans = self.spans.take().unwrap_or_default();
+        spans.locate(self.content);
+        spans
+    }
+
+    pub fn error(&self, kind: ErrorKind) -> Error {
+        self.error_at(kind, self.cursor)
+    }
+
+    // Creates an error for the byte at `position`
+    pub fn error_at(&self, kind: ErrorKind, position: usize) -> Error {
+        let located = self.locate(position);
+        if self.unclosed_comment == Some(position) {
+            return Error::at(ErrorKind::UnterminatedComment, self.content, located).with_expected(&["'*/'"]);
+        }
+        Error::at(kind, self.content, located)
+    }
+
+    fn locate(&self, offset: usize) -> Position {
+        let mut position = self.position.get();
+        position.move_to(self.content, offset);
+        self.position.set(position);
+        position
     }
 
     pub fn at_end(&self) -> bool {
@@ -30,31 +226,112 @@ impl State {
         self.cursor
     }
 
-    pub fn peek(&self) -> Option<&char> {
-        self.content.get(self.cursor)
+    pub fn len(&self) -> usize {
+        self.content.len()
     }
 
-    pub fn take(&mut self) -> Option<&char> {
-        match self.content.get(self.cursor) {
-            Some(character) => {
-                self.cursor += 1;
-                Some(character)
-            }
-            None => None,
+    // Moves back over the byte that was just taken
+    pub fn cursor_back(&mut self) {
+        self.cursor -= 1;
+    }
+
+    pub fn skip_to(&mut self, position: usize) {
+        self.cursor = position.min(self.content.len());
+    }
+
+    // Like `read_char` without skipping whitespace first
+    pub fn read_byte(&mut self, c: u8) -> bool {
+        let result = self.peek() == Some(c);
+        if result {
+            self.cursor += 1;
+        }
+        result
+    }
+
+    pub fn slice(&self, start: usize, end: usize) -> &'a [u8] {
+        &self.content[start..end]
+    }
+
+    pub fn peek(&self) -> Option<u8> {
+        self.content.get(self.cursor).copied()
+    }
+
+    pub fn take(&mut self) -> Option<u8> {
+        let result = self.peek();
+        if result.is_some() {
+            self.cursor += 1;
+        }
+        result
+    }
+
+    // Moves past the bytes for which `predicate` holds
+    pub fn skip_while(&mut self, predicate: impl Fn(u8) -> bool) {
+        while self.peek().is_some_and(&predicate) {
+            self.cursor += 1;
         }
     }
 
     // Return true if we're at the end of the string
     pub fn skip_whitespace(&mut self) -> bool {
-        while self.peek().is_some_and(|x| x.is_whitespace()) {
-            self.take();
+        self.skip_while(|x| matches!(x, b' ' | b'\t' | b'\n' | b'\r'));
+        if self.comments {
+            self.skip_comments();
         }
         self.at_end()
     }
 
-    pub fn read_char(&mut self, c : char) -> bool {
+    fn keep_comment(&mut self, start: usize, end: usize) {
+        if let Some(found) = &mut self.found_comments {
+            found.push((start, end));
+        }
+    }
+
+    // Skips comments and the wider whitespace of JSON5. An unclosed block comment is left in
+    // place unless recovering, so the parser stops there and reports it where it starts.
+    fn skip_comments(&mut self) {
+        loop {
+            let rest = &self.content[self.cursor..];
+            let start = self.cursor;
+            if rest.starts_with(b"//") {
+                self.skip_while(|x| x != b'\n');
+                // A \r before the line break is not part of the comment
+                let end = if self.content[..self.cursor].ends_with(b"\r") { self.cursor - 1 } else { self.cursor };
+                self.keep_comment(start, end);
+            } else if rest.starts_with(b"/*") {
+                match rest.windows(2).skip(2).position(|x| x == b"*/") {
+                    Some(end) => {
+                        self.cursor += end + 4;
+                        self.keep_comment(start, self.cursor);
+                    }
+                    None => {
+                        if !self.recovering {
+                            self.unclosed_comment = Some(start);
+                            return;
+                        }
+                        let _ = self.recover(self.error(ErrorKind::UnterminatedComment).with_expected(&["'*/'"]));
+                        self.cursor = self.content.len();
+                    }
+                }
+            } else if matches!(rest.first(), Some(b' ' | b'\t' | b'\n' | b'\r' | 0x0B | 0x0C)) {
+                self.cursor += 1;
+            } else if rest.first().is_some_and(|x| *x >= 0x80) {
+                // Unicode space separators, line separators and the byte order mark
+                let text = String::from_utf8_lossy(&rest[..rest.len().min(4)]);
+                match text.chars().next() {
+                    Some(c) if c != char::REPLACEMENT_CHARACTER && (c.is_whitespace() || c == '\u{FEFF}') => {
+                        self.cursor += c.len_utf8();
+                    }
+                    _ => return,
+                }
+            } else {
+                return;
+            }
+        }
+    }
+
+    pub fn read_char(&mut self, c : u8) -> bool {
         let mut result = false;
-        if !self.skip_whitespace() && self.peek() == Some(&c) {
+        if !self.skip_whitespace() && self.peek() == Some(c) {
             result = true;
             self.cursor += 1
         }
@@ -66,8 +343,8 @@ impl State {
             return false;
         }
 
-        for c in lit.chars() {
-            if self.peek() != Some(&c) {
+        for c in lit.bytes() {
+            if self.peek() != Some(c) {
                 return false;
             }
             self.cursor+=1;
@@ -77,48 +354,49 @@ impl State {
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod test {
     use super::*;
     #[test]
     fn test_state_read_char() {
-        let mut state_1 = State::new(" te ");
-        assert_eq!(true, state_1.read_char('t'));
-        assert_eq!(false, state_1.read_char('t'));
-        assert_eq!(true, state_1.read_char('e'));
-        assert_eq!(false, state_1.read_char('e'));
+        let mut state_1 = State::new(b" te ");
+        assert_eq!(true, state_1.read_char(b't'));
+        assert_eq!(false, state_1.read_char(b't'));
+        assert_eq!(true, state_1.read_char(b'e'));
+        assert_eq!(false, state_1.read_char(b'e'));
         assert_eq!(true, state_1.at_end());
     }
 
     #[test]
     fn test_state_read_literal() {
-        let mut state_1 = State::new("  true ");
+        let mut state_1 = State::new(b"  true ");
         assert_eq!(true, state_1.read_literal("true"));
 
 
-        let mut state_2 = State::new("  true ");
+        let mut state_2 = State::new(b"  true ");
         assert_eq!(false, state_2.read_literal("trx"));
 
-        let mut state_3 = State::new("false  true ");
+        let mut state_3 = State::new(b"false  true ");
         assert_eq!(true, state_3.read_literal("false"));
         assert_eq!(true, state_3.read_literal("true"));
     }
 
     #[test]
     fn test_state_skip_whitespace() {
-        let mut state_1 = State::new("test");
+        let mut state_1 = State::new(b"test");
         assert_eq!(false, state_1.skip_whitespace());
         assert_eq!(0, state_1.cursor);
         assert_eq!(false, state_1.at_end());
-        assert_eq!(Some(&'t'), state_1.peek());
+        assert_eq!(Some(b't'), state_1.peek());
 
-        let mut state_2 = State::new("   test");
+        let mut state_2 = State::new(b"   test");
         assert_eq!(false, state_2.skip_whitespace());
         assert_eq!(3, state_2.cursor);
         assert_eq!(false, state_2.at_end());
-        assert_eq!(Some(&'t'), state_2.peek());
+        assert_eq!(Some(b't'), state_2.peek());
 
-        let mut state_3 = State::new("t    ");
-        assert_eq!(Some(&'t'), state_3.take());
+        let mut state_3 = State::new(b"t    ");
+        assert_eq!(Some(b't'), state_3.take());
         assert_eq!(true, state_3.skip_whitespace());
         assert_eq!(true, state_3.at_end());
     }
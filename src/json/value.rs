@@ -1,10 +1,36 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops;
+use std::str::FromStr;
+use indexmap::IndexMap;
 use crate::json::{Array, Object};
+use crate::json::error::Error;
+use crate::json::number::Number;
+use crate::json::path::PathSegment;
+use crate::json::serializer::{self, SerializeOptions};
 
-#[derive(Debug, PartialEq, Clone)]
+/// A JSON value.
+///
+/// Values are built from Rust values with `From` and `collect`, read with [`str::parse`] and
+/// written with `Display`, on one line by default and indented with `{:#}`. Children are looked up
+/// with [`JsonValueType::get`] or by indexing, which gives `null` for children that do not exist.
+///
+/// ```
+/// use json_editor::json::value::JsonValueType;
+///
+/// let mut value: JsonValueType = "{\"name\" : \"x\", \"tags\" : [1]}".parse().unwrap();
+/// value["tags"].push(2);
+/// value["count"] = JsonValueType::from(2);
+/// assert_eq!(value["tags"][1], JsonValueType::from(2));
+/// assert!(value["missing"].is_null());
+/// assert_eq!(value.to_string(), "{\"name\" : \"x\", \"tags\" : [1, 2], \"count\" : 2}");
+/// ```
+#[derive(Debug, PartialEq, Clone, Default)]
 pub enum JsonValueType {
+    #[default]
     JsonTypeNull,
     JsonTypeBool(bool),
-    JsonTypeNumber(f64),
+    JsonTypeNumber(Number),
     JsonTypeObject(Object), // Is a vector with pairwise entries, key, value
     JsonTypeArray(Array), // Is a vector, all entries are plain
     JsonTypeString(String)
@@ -28,32 +54,99 @@ impl JsonValueType {
 
     pub fn as_number(&self) -> Option<f64> {
         match self {
-            JsonValueType::JsonTypeNumber(val) => Option::from(*val),
+            JsonValueType::JsonTypeNumber(val) => Option::from(val.as_f64()),
+            _ => None
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            JsonValueType::JsonTypeNumber(val) => val.as_i64(),
+            _ => None
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            JsonValueType::JsonTypeNumber(val) => val.as_u64(),
             _ => None
         }
     }
 
     pub fn is_null(&self) -> bool {
+        matches!(self, JsonValueType::JsonTypeNull)
+    }
+
+    pub fn as_array(&self) -> Option<&Array> {
         match self {
-            JsonValueType::JsonTypeNull => true,
-            _ => false
+            JsonValueType::JsonTypeArray(val) => Option::from(val),
+            _ => None
         }
     }
 
-    fn as_array(&self) -> Option<&Array> {
+    pub fn as_array_mut(&mut self) -> Option<&mut Array> {
         match self {
             JsonValueType::JsonTypeArray(val) => Option::from(val),
             _ => None
         }
     }
 
-    fn as_object(&self) -> Option<&Object> {
+    pub fn as_object(&self) -> Option<&Object> {
         match self {
             JsonValueType::JsonTypeObject(val) => Option::from(val),
             _ => None
         }
     }
 
+    pub fn as_object_mut(&mut self) -> Option<&mut Object> {
+        match self {
+            JsonValueType::JsonTypeObject(val) => Option::from(val),
+            _ => None
+        }
+    }
+
+    /// The element at an index of an array or the member with a key of an object, for repeated
+    /// keys the last one wins.
+    pub fn get<I: ValueIndex>(&self, index: I) -> Option<&JsonValueType> {
+        index.index_into(self)
+    }
+
+    pub fn get_mut<I: ValueIndex>(&mut self, index: I) -> Option<&mut JsonValueType> {
+        index.index_into_mut(self)
+    }
+
+    /// Replaces the value with `null` and returns it.
+    pub fn take(&mut self) -> JsonValueType {
+        std::mem::take(self)
+    }
+
+    /// Sets the member `key` of an object to `value` and returns the value it had, a new member
+    /// goes last. `null` is turned into an empty object first.
+    ///
+    /// Panics if the value is neither an object nor `null`.
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<JsonValueType>) -> Option<JsonValueType> {
+        if self.is_null() {
+            *self = JsonValueType::JsonTypeObject(Object::new());
+        }
+        match self {
+            JsonValueType::JsonTypeObject(Object(members)) => members.insert(key.into(), value.into()),
+            _ => panic!("can not insert a member into {}", self.kind()),
+        }
+    }
+
+    /// Adds `value` to the end of an array. `null` is turned into an empty array first.
+    ///
+    /// Panics if the value is neither an array nor `null`.
+    pub fn push(&mut self, value: impl Into<JsonValueType>) {
+        if self.is_null() {
+            *self = JsonValueType::JsonTypeArray(Array(Vec::new()));
+        }
+        match self {
+            JsonValueType::JsonTypeArray(Array(values)) => values.push(value.into()),
+            _ => panic!("can not push an element onto {}", self.kind()),
+        }
+    }
+
     pub fn len(&self) -> Option<usize> {
         match self {
             JsonValueType::JsonTypeObject(Object(vec)) => Option::from(vec.len()),
@@ -62,48 +155,270 @@ impl JsonValueType {
         }
     }
 
-    pub fn to_string(&self) -> String {
-        match &self {
-            JsonValueType::JsonTypeNull => "null".to_string(),
-            JsonValueType::JsonTypeBool(val) => {
-                if *val { "true".to_string() }
-                else { "false".to_string() }
-            }
-            JsonValueType::JsonTypeNumber(val) => { val.to_string() }
-            JsonValueType::JsonTypeObject(Object(val)) => {
-                    let mut result = "{".to_string();
-                    for (index,(key,value)) in val.iter().enumerate() {
-                        result = format!("{}\"{}\" : {}",result, key, value.to_string());
-                        if index < val.len() - 1 {
-                            result += ", ";
-                        }
-                    }
-                result + "}\n"
-            }
-            JsonValueType::JsonTypeArray(Array(val)) => {
-                    let mut result : String = "[".to_string();
-                    for (i, item) in val.iter().enumerate()
-                    {
-                        if i != 0 {
-                            result = format!("{}, {}", result, item.to_string());
-                        } else {
-                            result = format!("{}{}", result, item.to_string());
-                        }
-                    }
-
-                    result + "]\n"
+    pub fn is_empty(&self) -> bool {
+        self.len() == Some(0)
+    }
+
+    // For panic messages
+    fn kind(&self) -> &'static str {
+        match self {
+            JsonValueType::JsonTypeNull => "null",
+            JsonValueType::JsonTypeBool(_) => "a boolean",
+            JsonValueType::JsonTypeNumber(_) => "a number",
+            JsonValueType::JsonTypeObject(_) => "an object",
+            JsonValueType::JsonTypeArray(_) => "an array",
+            JsonValueType::JsonTypeString(_) => "a string",
+        }
+    }
+}
+
+/// Writes JSON on one line, or indented JSON with `{:#}`. Infinite and NaN numbers, which JSON can not
+/// represent, are written as `null`, use [`super::serializer`] to have them reported instead.
+impl fmt::Display for JsonValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            return f.write_str(&serializer::to_string_lossy(self, &SerializeOptions::new()));
+        }
+        let mut out = String::new();
+        serializer::write_inline(&mut out, self);
+        f.write_str(&out)
+    }
+}
+
+/// Parses strict JSON like [`super::to_object`].
+impl FromStr for JsonValueType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        super::to_object(s)
+    }
+}
+
+/// Something that selects a child of a [`JsonValueType`]: a `usize` for an element of an array,
+/// a string for a member of an object, or a [`PathSegment`] for either.
+pub trait ValueIndex {
+    fn index_into<'a>(&self, value: &'a JsonValueType) -> Option<&'a JsonValueType>;
+
+    fn index_into_mut<'a>(&self, value: &'a mut JsonValueType) -> Option<&'a mut JsonValueType>;
+
+    /// Used by `IndexMut`, which adds members that are missing and turns `null` into an object.
+    /// Panics when there is no such child and it can not be added.
+    fn index_or_insert<'a>(&self, value: &'a mut JsonValueType) -> &'a mut JsonValueType;
+}
+
+impl ValueIndex for usize {
+    fn index_into<'a>(&self, value: &'a JsonValueType) -> Option<&'a JsonValueType> {
+        value.as_array()?.0.get(*self)
+    }
+
+    fn index_into_mut<'a>(&self, value: &'a mut JsonValueType) -> Option<&'a mut JsonValueType> {
+        value.as_array_mut()?.0.get_mut(*self)
+    }
+
+    fn index_or_insert<'a>(&self, value: &'a mut JsonValueType) -> &'a mut JsonValueType {
+        match value {
+            JsonValueType::JsonTypeArray(Array(values)) => {
+                let len = values.len();
+                values.get_mut(*self).unwrap_or_else(|| panic!("index {} is out of range for an array of length {}", self, len))
             }
-            JsonValueType::JsonTypeString(val) => { format!("\"{}\"", val) }
+            _ => panic!("can not index {} with {}", value.kind(), self),
+        }
+    }
+}
+
+impl ValueIndex for str {
+    fn index_into<'a>(&self, value: &'a JsonValueType) -> Option<&'a JsonValueType> {
+        value.as_object()?.0.get(self)
+    }
+
+    fn index_into_mut<'a>(&self, value: &'a mut JsonValueType) -> Option<&'a mut JsonValueType> {
+        value.as_object_mut()?.0.get_mut(self)
+    }
+
+    fn index_or_insert<'a>(&self, value: &'a mut JsonValueType) -> &'a mut JsonValueType {
+        if value.is_null() {
+            *value = JsonValueType::JsonTypeObject(Object::new());
+        }
+        match value {
+            JsonValueType::JsonTypeObject(Object(members)) => members.entry(self.to_string()).or_default(),
+            _ => panic!("can not index {} with \"{}\"", value.kind(), self),
         }
     }
 }
 
+impl ValueIndex for String {
+    fn index_into<'a>(&self, value: &'a JsonValueType) -> Option<&'a JsonValueType> {
+        self.as_str().index_into(value)
+    }
+
+    fn index_into_mut<'a>(&self, value: &'a mut JsonValueType) -> Option<&'a mut JsonValueType> {
+        self.as_str().index_into_mut(value)
+    }
+
+    fn index_or_insert<'a>(&self, value: &'a mut JsonValueType) -> &'a mut JsonValueType {
+        self.as_str().index_or_insert(value)
+    }
+}
+
+impl ValueIndex for PathSegment {
+    fn index_into<'a>(&self, value: &'a JsonValueType) -> Option<&'a JsonValueType> {
+        match self {
+            PathSegment::Key(key) => key.index_into(value),
+            PathSegment::Index(index) => index.index_into(value),
+        }
+    }
+
+    fn index_into_mut<'a>(&self, value: &'a mut JsonValueType) -> Option<&'a mut JsonValueType> {
+        match self {
+            PathSegment::Key(key) => key.index_into_mut(value),
+            PathSegment::Index(index) => index.index_into_mut(value),
+        }
+    }
+
+    fn index_or_insert<'a>(&self, value: &'a mut JsonValueType) -> &'a mut JsonValueType {
+        match self {
+            PathSegment::Key(key) => key.index_or_insert(value),
+            PathSegment::Index(index) => index.index_or_insert(value),
+        }
+    }
+}
+
+impl<T: ValueIndex + ?Sized> ValueIndex for &T {
+    fn index_into<'a>(&self, value: &'a JsonValueType) -> Option<&'a JsonValueType> {
+        (**self).index_into(value)
+    }
+
+    fn index_into_mut<'a>(&self, value: &'a mut JsonValueType) -> Option<&'a mut JsonValueType> {
+        (**self).index_into_mut(value)
+    }
+
+    fn index_or_insert<'a>(&self, value: &'a mut JsonValueType) -> &'a mut JsonValueType {
+        (**self).index_or_insert(value)
+    }
+}
+
+// What indexing gives for children that do not exist
+static NULL: JsonValueType = JsonValueType::JsonTypeNull;
+
+impl<I: ValueIndex> ops::Index<I> for JsonValueType {
+    type Output = JsonValueType;
+
+    fn index(&self, index: I) -> &JsonValueType {
+        index.index_into(self).unwrap_or(&NULL)
+    }
+}
+
+impl<I: ValueIndex> ops::IndexMut<I> for JsonValueType {
+    fn index_mut(&mut self, index: I) -> &mut JsonValueType {
+        index.index_or_insert(self)
+    }
+}
+
+impl From<bool> for JsonValueType {
+    fn from(value: bool) -> Self {
+        JsonValueType::JsonTypeBool(value)
+    }
+}
+
+impl From<Number> for JsonValueType {
+    fn from(value: Number) -> Self {
+        JsonValueType::JsonTypeNumber(value)
+    }
+}
+
+macro_rules! from_number {
+    ($($source:ty),*) => {
+        $(
+            impl From<$source> for JsonValueType {
+                fn from(value: $source) -> Self {
+                    JsonValueType::JsonTypeNumber(Number::from(value))
+                }
+            }
+        )*
+    };
+}
+
+from_number!(i32, i64, u32, u64, usize, f64);
+
+impl From<&str> for JsonValueType {
+    fn from(value: &str) -> Self {
+        JsonValueType::JsonTypeString(value.to_string())
+    }
+}
+
+impl From<String> for JsonValueType {
+    fn from(value: String) -> Self {
+        JsonValueType::JsonTypeString(value)
+    }
+}
+
+impl From<Array> for JsonValueType {
+    fn from(value: Array) -> Self {
+        JsonValueType::JsonTypeArray(value)
+    }
+}
+
+impl From<Object> for JsonValueType {
+    fn from(value: Object) -> Self {
+        JsonValueType::JsonTypeObject(value)
+    }
+}
+
+/// `None` becomes `null`.
+impl<T: Into<JsonValueType>> From<Option<T>> for JsonValueType {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(JsonValueType::JsonTypeNull, Into::into)
+    }
+}
+
+impl<T: Into<JsonValueType>> From<Vec<T>> for JsonValueType {
+    fn from(value: Vec<T>) -> Self {
+        value.into_iter().collect()
+    }
+}
+
+/// The members are in the order the map iterates them, which for a `HashMap` is arbitrary.
+impl<K: Into<String>, V: Into<JsonValueType>> From<HashMap<K, V>> for JsonValueType {
+    fn from(value: HashMap<K, V>) -> Self {
+        value.into_iter().collect()
+    }
+}
+
+impl<K: Into<String>, V: Into<JsonValueType>> From<IndexMap<K, V>> for JsonValueType {
+    fn from(value: IndexMap<K, V>) -> Self {
+        value.into_iter().collect()
+    }
+}
+
+/// Collects into an array.
+impl<T: Into<JsonValueType>> FromIterator<T> for JsonValueType {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        JsonValueType::JsonTypeArray(Array(iter.into_iter().map(Into::into).collect()))
+    }
+}
+
+/// Collects key and value pairs into an object, a repeated key keeps the position of its first
+/// pair and the value of its last.
+impl<K: Into<String>, V: Into<JsonValueType>> FromIterator<(K, V)> for JsonValueType {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        JsonValueType::JsonTypeObject(Object(iter.into_iter().map(|(key, value)| (key.into(), value.into())).collect()))
+    }
+}
+
 
 #[cfg(test)]
 mod test {
     use super::*;
     #[test]
     fn test_to_string_array() {
+        let value = JsonValueType::JsonTypeArray(Array(vec![JsonValueType::JsonTypeNull, JsonValueType::JsonTypeBool(true)]));
+        assert_eq!(value.to_string(), "[null, true]");
+    }
 
+    #[test]
+    fn test_to_string_escapes() {
+        let value = JsonValueType::JsonTypeArray(Array(vec![JsonValueType::JsonTypeString("a\"\\\n\u{1}".to_string()),
+            JsonValueType::JsonTypeNumber(Number::from(f64::NAN))]));
+        assert_eq!(value.to_string(), r#"["a\"\\\n\u0001", null]"#);
     }
 }
\ No newline at end of file
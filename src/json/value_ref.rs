@@ -0,0 +1,102 @@
+use std::borrow::Cow;
+use indexmap::IndexMap;
+use crate::json::{Array, Object};
+use crate::json::number::Number;
+use crate::json::value::JsonValueType;
+
+/// A read-only view of a parsed document that borrows from the input.
+///
+/// Strings and keys only allocate when they contain escapes. Object members are kept in source
+/// order, including repeated keys.
+#[derive(Debug, PartialEq, Clone)]
+pub enum JsonValueRef<'a> {
+    JsonTypeNull,
+    JsonTypeBool(bool),
+    JsonTypeNumber(Number),
+    JsonTypeObject(Vec<(Cow<'a, str>, JsonValueRef<'a>)>),
+    JsonTypeArray(Vec<JsonValueRef<'a>>),
+    JsonTypeString(Cow<'a, str>)
+}
+
+impl<'a> JsonValueRef<'a> {
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonValueRef::JsonTypeBool(val) => Some(*val),
+            _ => None
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValueRef::JsonTypeString(val) => Some(val),
+            _ => None
+        }
+    }
+
+    pub fn as_number(&self) -> Option<&Number> {
+        match self {
+            JsonValueRef::JsonTypeNumber(val) => Some(val),
+            _ => None
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, JsonValueRef::JsonTypeNull)
+    }
+
+    pub fn as_array(&self) -> Option<&[JsonValueRef<'a>]> {
+        match self {
+            JsonValueRef::JsonTypeArray(val) => Some(val),
+            _ => None
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&[(Cow<'a, str>, JsonValueRef<'a>)]> {
+        match self {
+            JsonValueRef::JsonTypeObject(val) => Some(val),
+            _ => None
+        }
+    }
+
+    /// Looks up `key` in an object, for repeated keys the last one wins as in [`JsonValueType`].
+    pub fn get(&self, key: &str) -> Option<&JsonValueRef<'a>> {
+        self.as_object()?.iter().rev().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// Copies the value into an owned, editable [`JsonValueType`].
+    pub fn into_owned(self) -> JsonValueType {
+        match self {
+            JsonValueRef::JsonTypeNull => JsonValueType::JsonTypeNull,
+            JsonValueRef::JsonTypeBool(val) => JsonValueType::JsonTypeBool(val),
+            JsonValueRef::JsonTypeNumber(val) => JsonValueType::JsonTypeNumber(val),
+            JsonValueRef::JsonTypeString(val) => JsonValueType::JsonTypeString(val.into_owned()),
+            JsonValueRef::JsonTypeArray(val) => {
+                JsonValueType::JsonTypeArray(Array(val.into_iter().map(JsonValueRef::into_owned).collect()))
+            }
+            JsonValueRef::JsonTypeObject(val) => {
+                let map: IndexMap<String, JsonValueType> = val.into_iter()
+                    .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                    .collect();
+                JsonValueType::JsonTypeObject(Object(map))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_into_owned() {
+        let value = JsonValueRef::JsonTypeObject(vec![
+            (Cow::Borrowed("a"), JsonValueRef::JsonTypeArray(vec![JsonValueRef::JsonTypeNull])),
+            (Cow::Borrowed("a"), JsonValueRef::JsonTypeString(Cow::Borrowed("last")))
+        ]);
+        assert_eq!(value.get("a"), Some(&JsonValueRef::JsonTypeString(Cow::Borrowed("last"))));
+        assert_eq!(value.into_owned(), JsonValueType::JsonTypeObject(Object(IndexMap::from([
+            ("a".to_string(), JsonValueType::JsonTypeString("last".to_string()))
+        ]))));
+    }
+}
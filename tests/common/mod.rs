@@ -0,0 +1,28 @@
+//! Helpers shared by the integration tests, each test crate uses some of them.
+#![allow(dead_code)]
+
+use json_editor::json::error::ErrorKind;
+use json_editor::json::number::Number;
+use json_editor::json::options::ParseOptions;
+use json_editor::json::path::PathSegment;
+use json_editor::json::value::JsonValueType;
+use json_editor::json::value::JsonValueType::JsonTypeNumber;
+use json_editor::json::{to_object, to_object_with};
+
+pub fn number(value: i64) -> JsonValueType {
+    JsonTypeNumber(Number::from(value))
+}
+
+pub fn key(name: &str) -> PathSegment {
+    PathSegment::Key(name.to_string())
+}
+
+pub fn error_of(json: &str) -> (ErrorKind, usize) {
+    let error = to_object(json).unwrap_err();
+    (error.kind(), error.offset())
+}
+
+pub fn error_with(json: &str, options: &ParseOptions) -> (ErrorKind, usize) {
+    let error = to_object_with(json, options).unwrap_err();
+    (error.kind(), error.offset())
+}
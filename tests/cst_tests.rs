@@ -0,0 +1,73 @@
+use indexmap::IndexMap;
+use json_editor::json::cst::{Document, EditError};
+use json_editor::json::error::ErrorKind::MissingComma;
+use json_editor::json::number::Number;
+use json_editor::json::options::{Dialect, ParseOptions};
+use json_editor::json::path::{Path, PathSegment};
+use json_editor::json::value::JsonValueType::{JsonTypeArray, JsonTypeBool, JsonTypeNumber, JsonTypeObject, JsonTypeString};
+use json_editor::json::{to_object, Array, Object};
+
+mod common;
+use common::key;
+
+fn path(segments: &[PathSegment]) -> Path {
+    Path(segments.to_vec())
+}
+
+const SOURCE: &str = "\u{FEFF}// settings\n{\n  \"b\"  :  1.50 , \"z\" : [ 1,2 ], // tail\n  \"a\":\"\\u0041\", \"b\" : 1e2\n}\n\n";
+
+#[test]
+fn test_round_trip() {
+    let options = ParseOptions::new().with_dialect(Dialect::Json5);
+    for source in [SOURCE, "[]", " [ ] ", "{}", "[1, 2, /* end */]", "{a : 'x', \"b\" : [ {} ,] ,}", "0x1F", "\"\\\"\""] {
+        let document = Document::parse_with(source, &options).unwrap();
+        assert_eq!(document.to_string(), source);
+    }
+    assert_eq!(Document::parse("[1 2]").map_err(|x| x.kind()), Err(MissingComma));
+}
+
+#[test]
+fn test_update_keeps_text() {
+    let options = ParseOptions::new().with_dialect(Dialect::Jsonc);
+    let mut document = Document::parse_with(SOURCE, &options).unwrap();
+    let mut value = to_object(&SOURCE[SOURCE.find('{').unwrap()..].replace("// tail", "")).unwrap();
+
+    // The same value
+    document.update(&value).unwrap();
+    assert_eq!(document.to_string(), SOURCE);
+
+    // Repeated keys have the value of the last member, which is the only one that is updated
+    assert_eq!(document.get(&path(&[key("b")])).unwrap().to_string(), "1e2");
+    assert_eq!(document.get(&path(&[key("z"), PathSegment::Index(1)])).unwrap().to_string(), "2");
+
+    let JsonTypeObject(Object(members)) = &mut value else { panic!() };
+    members.insert("a".to_string(), JsonTypeString("B".to_string()));
+    members.insert("new".to_string(), JsonTypeBool(true));
+    members.insert("b".to_string(), JsonTypeNumber(Number::from(100)));
+    members.insert("z".to_string(), JsonTypeArray(Array(vec![JsonTypeNumber(Number::from(1))])));
+    document.update(&value).unwrap();
+    assert_eq!(document.to_string(), "\u{FEFF}// settings\n{\n  \"b\"  :  1.50 , \"z\" : [ 1 ], // tail\n  \"a\":\"B\", \"b\" : 1e2, \"new\" : true\n}\n\n");
+}
+
+#[test]
+fn test_edits() {
+    let mut document = Document::parse("{\n    \"list\" : [\n        1,\n        2\n    ],\n    \"empty\" : {}\n}").unwrap();
+    let list = path(&[key("list")]);
+    document.set(&list.join(PathSegment::Index(2)), &JsonTypeNumber(Number::from(3))).unwrap();
+    document.set(&path(&[key("empty"), key("x")]), &JsonTypeObject(Object(IndexMap::new()))).unwrap();
+    assert_eq!(document.to_string(), "{\n    \"list\" : [\n        1,\n        2,\n        3\n    ],\n    \"empty\" : {\"x\": {}}\n}");
+
+    document.remove(&list.join(PathSegment::Index(0))).unwrap();
+    document.remove(&list.join(PathSegment::Index(1))).unwrap();
+    document.remove(&path(&[key("empty")])).unwrap();
+    assert_eq!(document.to_string(), "{\n    \"list\" : [\n        2\n    ]\n}");
+
+    assert_eq!(document.set(&list.join(PathSegment::Index(5)), &JsonTypeBool(true)), Err(EditError::NotFound(list.join(PathSegment::Index(5)))));
+    assert_eq!(document.set(&list.join(key("x")), &JsonTypeBool(true)), Err(EditError::NotFound(list.join(key("x")))));
+    assert_eq!(document.remove(&Path::root()), Err(EditError::RemoveRoot));
+
+    let nan = JsonTypeArray(Array(vec![JsonTypeNumber(Number::from(1)), JsonTypeNumber(Number::from(f64::NAN))]));
+    assert_eq!(document.set(&list, &nan), Err(EditError::NonFiniteNumber(list.clone())));
+    assert_eq!(document.update(&JsonTypeNumber(Number::from(f64::INFINITY))), Err(EditError::NonFiniteNumber(Path::root())));
+    assert_eq!(document.to_string(), "{\n    \"list\" : [\n        2\n    ]\n}");
+}
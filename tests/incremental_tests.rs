@@ -0,0 +1,77 @@
+use json_editor::json::error::ErrorKind::{EndOfInput, MissingComma, TrailingCharacters};
+use json_editor::json::incremental::{Reparsed, TextEdit, Tree};
+use json_editor::json::options::{Dialect, ParseOptions};
+use json_editor::json::path::{Path, PathSegment};
+use json_editor::json::to_object_with_spans;
+
+fn edit(range: std::ops::Range<usize>, text: &str) -> TextEdit {
+    TextEdit { range, text: text.to_string() }
+}
+
+// The tree has to end up as if the new source had been parsed from scratch
+fn assert_up_to_date(tree: &Tree) {
+    let (value, spans) = to_object_with_spans(tree.source()).unwrap();
+    assert_eq!(tree.value(), Some(&value));
+    assert_eq!(tree.spans(), &spans);
+}
+
+const SOURCE: &str = "{\n  \"a\" : [1, {\"x\" : \"é\"}],\n  \"b\" : {\"c\" : [true]}, \"d\" : 2\n}";
+
+#[test]
+fn test_reparse_container() {
+    let mut tree = Tree::new(SOURCE.to_string(), &ParseOptions::default()).unwrap();
+    let a = Path::root().join(PathSegment::Key("a".to_string()));
+
+    // Replacing "é" with a longer value that spans lines moves everything after it
+    let start = SOURCE.find("\"é\"").unwrap();
+    let result = tree.apply(&edit(start..start + 4, "[\n 1,\n 2]"));
+    assert_eq!(result, Ok(Reparsed::Container(a.join(PathSegment::Index(1)))));
+    assert_up_to_date(&tree);
+
+    // Inserting on the line of the edit moves the columns of what follows on that line
+    let start = tree.source().find("[true]").unwrap() + 1;
+    let result = tree.apply(&edit(start..start, "null, "));
+    assert_eq!(result, Ok(Reparsed::Container(Path::root().join(PathSegment::Key("b".to_string())).join(PathSegment::Key("c".to_string())))));
+    assert_up_to_date(&tree);
+
+    let start = tree.source().find("\"d\"").unwrap();
+    assert_eq!(tree.apply(&edit(start..start + 3, "\"e\"")), Ok(Reparsed::Container(Path::root())));
+    assert_up_to_date(&tree);
+}
+
+#[test]
+fn test_full_parse() {
+    let mut tree = Tree::new("[[1], [2]]".to_string(), &ParseOptions::default()).unwrap();
+
+    // [1] no longer parses on its own, and neither does the document
+    let error = tree.apply(&edit(3..3, "]")).unwrap_err();
+    assert_eq!((error.kind(), error.offset()), (TrailingCharacters, 5));
+    assert_eq!(tree.value(), None);
+    assert_eq!(tree.apply(&edit(3..4, "")), Ok(Reparsed::Full));
+    assert_up_to_date(&tree);
+
+    // The brackets of the top-level value are part of the edit
+    assert_eq!(tree.apply(&edit(0..1, "[0, ")), Ok(Reparsed::Full));
+    assert_eq!(tree.source(), "[0, [1], [2]]");
+    assert_up_to_date(&tree);
+
+    let mut tree = Tree::new("[[1], 2]".to_string(), &ParseOptions::default()).unwrap();
+    assert_eq!(tree.apply(&edit(3..3, " 3")).map_err(|x| x.kind()), Err(MissingComma));
+}
+
+#[test]
+fn test_depth_and_dialect() {
+    let options = ParseOptions::new().with_dialect(Dialect::Jsonc).with_max_depth(3);
+    let mut tree = Tree::new("[[/* one */ []]]".to_string(), &options).unwrap();
+    assert!(tree.apply(&edit(13..13, "[]")).is_err());
+    assert_eq!(tree.apply(&edit(13..15, "1,")), Ok(Reparsed::Full));
+    assert_eq!(tree.source(), "[[/* one */ [1,]]]");
+
+    // Commenting out the closing bracket ends the array somewhere else
+    let options = ParseOptions::new().with_dialect(Dialect::Jsonc);
+    let mut tree = Tree::new("{\"a\" : [1, 2], \"b\" : 3}".to_string(), &options).unwrap();
+    let error = tree.apply(&edit(9..12, "] //")).unwrap_err();
+    assert_eq!(tree.source(), "{\"a\" : [1] //], \"b\" : 3}");
+    assert_eq!(error.kind(), EndOfInput);
+    assert_eq!(tree.value(), None);
+}
@@ -0,0 +1,99 @@
+use indexmap::IndexMap;
+use json_editor::json::error::ErrorKind::*;
+use json_editor::json::{Array, Object, to_object, to_object_recovering_with, to_object_with};
+use json_editor::json::number::Number;
+use json_editor::json::options::{Dialect, ParseOptions};
+use json_editor::json::value::JsonValueType;
+use json_editor::json::value::JsonValueType::{JsonTypeArray, JsonTypeBool, JsonTypeNumber, JsonTypeObject, JsonTypeString};
+
+mod common;
+use common::error_with;
+
+fn json5() -> ParseOptions {
+    ParseOptions::new().with_dialect(Dialect::Json5)
+}
+
+fn parse(json: &str) -> JsonValueType {
+    to_object_with(json, &json5()).unwrap()
+}
+
+fn number_of(json: &str) -> Number {
+    match parse(json) {
+        JsonTypeNumber(number) => number,
+        other => panic!("not a number: {:?}", other)
+    }
+}
+
+#[test]
+fn test_json5_document() {
+    let json = r#"
+        // Comments are whitespace
+        {
+            unquoted: 'single "quoted"',
+            $id_2: [1, 2,], /* trailing commas */
+            "quoted": {a: true,},
+        }"#;
+    assert_eq!(parse(json), JsonTypeObject(Object(IndexMap::from([
+        ("unquoted".to_string(), JsonTypeString("single \"quoted\"".to_string())),
+        ("$id_2".to_string(), JsonTypeArray(Array(vec![JsonTypeNumber(Number::from(1)), JsonTypeNumber(Number::from(2))]))),
+        ("quoted".to_string(), JsonTypeObject(Object(IndexMap::from([("a".to_string(), JsonTypeBool(true))])))),
+    ]))));
+
+    // None of it is strict JSON
+    assert_eq!(to_object("[1, 2,]").unwrap_err().kind(), UnexpectedCharacter);
+    assert_eq!(to_object("// x\n1").unwrap_err().kind(), UnexpectedCharacter);
+    assert_eq!(to_object("{a : 1}").unwrap_err().kind(), ExpectedKey);
+    assert_eq!(to_object("'a'").unwrap_err().kind(), UnexpectedCharacter);
+}
+
+#[test]
+fn test_json5_numbers() {
+    assert_eq!(number_of("0x1F").as_u64(), Some(31));
+    assert_eq!(number_of("-0XfF").as_i64(), Some(-255));
+    assert_eq!(number_of("0x1F").lexeme(), Some("0x1F"));
+    assert_eq!(number_of("+1").as_u64(), Some(1));
+    assert_eq!(number_of(".5").as_f64(), 0.5);
+    assert_eq!(number_of("5.").as_f64(), 5.0);
+    assert_eq!(number_of("5.").to_string(), "5.");
+    assert_eq!(number_of("-Infinity").as_f64(), f64::NEG_INFINITY);
+    assert_eq!(number_of("+Infinity").as_f64(), f64::INFINITY);
+    assert!(number_of("NaN").as_f64().is_nan());
+    assert_eq!(number_of("1.5e3").as_f64(), 1500.0);
+
+    assert_eq!(error_with("0x", &json5()), (InvalidNumber, 2));
+    assert_eq!(error_with(".", &json5()), (InvalidNumber, 1));
+    assert_eq!(error_with("Infinit", &json5()), (EndOfInput, 7));
+    assert_eq!(error_with("-Infinite", &json5()), (InvalidNumber, 8));
+    assert_eq!(error_with("012", &json5()), (InvalidNumber, 1));
+}
+
+#[test]
+fn test_json5_strings() {
+    assert_eq!(parse(r#"'it\'s'"#), JsonTypeString("it's".to_string()));
+    assert_eq!(parse(r#""\x41\v\0\q""#), JsonTypeString("A\u{b}\0q".to_string()));
+    assert_eq!(parse("'line \\\ncontinued'"), JsonTypeString("line continued".to_string()));
+    assert_eq!(error_with(r#""\1""#, &json5()), (InvalidEscape, 2));
+    assert_eq!(error_with(r#""\xZ1""#, &json5()), (InvalidEscape, 3));
+}
+
+#[test]
+fn test_json5_comments() {
+    assert_eq!(parse("/* a */ [ // b\n 1 /**/ ] // c"), JsonTypeArray(Array(vec![JsonTypeNumber(Number::from(1))])));
+    assert_eq!(parse("\u{feff}\u{a0}true\u{2028}"), JsonTypeBool(true));
+    // An unclosed comment is reported where it starts
+    assert_eq!(error_with("[1, /* 2 ]", &json5()), (UnterminatedComment, 4));
+    assert_eq!(error_with("[1] /* x", &json5()), (UnterminatedComment, 4));
+    assert_eq!(error_with("{\"a\" : 1 /* x", &json5()), (UnterminatedComment, 9));
+
+    let recovered = to_object_recovering_with("[1] /* ", &json5());
+    let errors: Vec<_> = recovered.diagnostics.iter().map(|x| (x.error.kind(), x.error.offset())).collect();
+    assert_eq!(errors, vec![(UnterminatedComment, 4)]);
+}
+
+#[test]
+fn test_json5_errors() {
+    assert_eq!(error_with("[1,,]", &json5()), (UnexpectedCharacter, 3));
+    assert_eq!(error_with("{a: 1,,}", &json5()), (ExpectedKey, 6));
+    assert_eq!(error_with("{a 1}", &json5()), (MissingColon, 3));
+    assert_eq!(error_with("[1 2]", &json5()), (MissingComma, 3));
+}
@@ -0,0 +1,82 @@
+use json_editor::json::comments::Comment;
+use json_editor::json::error::ErrorKind::UnexpectedCharacter;
+use json_editor::json::{to_object_recovering_with, to_object_with_comments};
+use json_editor::json::options::{Dialect, ParseOptions};
+use json_editor::json::path::{Path, PathSegment};
+use json_editor::json::serializer::{to_string_with_comments, SerializeOptions};
+
+mod common;
+use common::key;
+
+fn jsonc() -> ParseOptions {
+    ParseOptions::new().with_dialect(Dialect::Jsonc)
+}
+
+fn texts(comments: &[Comment]) -> Vec<&str> {
+    comments.iter().map(|x| x.text.as_str()).collect()
+}
+
+const SETTINGS: &str = r#"// Editor settings
+{
+    // Size in points
+    "editor.fontSize": 14, // not too small
+    "files.exclude": {
+        "**/.git": true,
+        /* more to come */
+    },
+    "list": [1, /* two */ 2],
+} // end
+"#;
+
+#[test]
+fn test_jsonc_comments() {
+    let (_, comments) = to_object_with_comments(SETTINGS, &jsonc()).unwrap();
+    assert_eq!(comments.len(), 6);
+
+    let root = comments.get(&Path::root()).unwrap();
+    assert_eq!(texts(&root.leading), vec!["// Editor settings"]);
+    assert_eq!(texts(&root.trailing), vec!["// end"]);
+
+    let font_size = comments.get(&Path::root().join(key("editor.fontSize"))).unwrap();
+    assert_eq!(texts(&font_size.leading), vec!["// Size in points"]);
+    assert_eq!(texts(&font_size.trailing), vec!["// not too small"]);
+
+    assert_eq!(texts(&comments.get(&Path::root().join(key("files.exclude"))).unwrap().inner), vec!["/* more to come */"]);
+    // Comments on the same line belong to the value before them
+    let one = Path::root().join(key("list")).join(PathSegment::Index(0));
+    assert_eq!(texts(&comments.get(&one).unwrap().trailing), vec!["/* two */"]);
+}
+
+#[test]
+fn test_jsonc_round_trip() {
+    let (value, comments) = to_object_with_comments(SETTINGS, &jsonc()).unwrap();
+    let written = to_string_with_comments(&value, &comments, &SerializeOptions::new()).unwrap();
+    assert_eq!(written, r#"// Editor settings
+{
+    // Size in points
+    "editor.fontSize": 14, // not too small
+    "files.exclude": {
+        "**/.git": true
+        /* more to come */
+    },
+    "list": [
+        1, /* two */
+        2
+    ]
+} // end"#);
+
+    // Writing again gives the same text
+    let (value, comments) = to_object_with_comments(&written, &jsonc()).unwrap();
+    assert_eq!(to_string_with_comments(&value, &comments, &SerializeOptions::new()).unwrap(), written);
+}
+
+#[test]
+fn test_jsonc_is_not_json5() {
+    let error = to_object_with_comments("{a : 1}", &jsonc()).unwrap_err();
+    assert_eq!(error.offset(), 1);
+    assert_eq!(to_object_with_comments("'a'", &jsonc()).unwrap_err().kind(), UnexpectedCharacter);
+
+    let recovered = to_object_recovering_with("[1, // one\n 2]", &jsonc());
+    assert!(recovered.diagnostics.is_empty());
+    assert_eq!(texts(&recovered.comments.get(&Path(vec![PathSegment::Index(0)])).unwrap().trailing), vec!["// one"]);
+}
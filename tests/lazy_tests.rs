@@ -0,0 +1,101 @@
+use std::fs;
+use json_editor::json::error::Error;
+use json_editor::json::error::ErrorKind::{Cancelled, EndOfInput, MissingComma, TrailingCharacters, UnexpectedCharacter};
+use json_editor::json::lazy::{LazyDocument, LazyValue};
+use json_editor::json::number::Number;
+use json_editor::json::options::{Dialect, ParseOptions};
+use json_editor::json::progress::Progress;
+use json_editor::json::value::JsonValueType::{JsonTypeArray, JsonTypeNumber, JsonTypeString};
+use json_editor::json::{to_object, Array};
+
+fn lazy(json: &str) -> LazyDocument {
+    LazyDocument::from_bytes(json.as_bytes().to_vec(), &ParseOptions::default()).unwrap()
+}
+
+fn error_of(json: &str) -> (json_editor::json::error::ErrorKind, usize) {
+    let error = LazyDocument::from_bytes(json.as_bytes().to_vec(), &ParseOptions::default()).err().unwrap();
+    (error.kind(), error.offset())
+}
+
+#[test]
+fn test_index() {
+    let json = r#"{"a" : [1, "]", {"b" : []}], "c" : "x"}"#;
+    let document = lazy(json);
+    assert_eq!(document.containers(), 4);
+    let LazyValue::Container(root) = document.root().unwrap() else { panic!() };
+    assert_eq!((root.start, root.end, root.is_object, root.len), (0, json.len(), true, 2));
+
+    let children = document.children(&root).collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(children[1], (Some("c".to_string()), LazyValue::Scalar(JsonTypeString("x".to_string()))));
+    let (key, LazyValue::Container(a)) = &children[0] else { panic!() };
+    assert_eq!((key.as_deref(), a.len), (Some("a"), 3));
+    let elements = document.children(a).collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(elements[0], (None, LazyValue::Scalar(JsonTypeNumber(Number::from(1)))));
+    assert!(matches!(elements[2].1, LazyValue::Container(x) if x.len == 1 && x.is_object));
+    assert_eq!(document.value(a).unwrap(), to_object(&json[a.start..a.end]).unwrap());
+
+    assert_eq!(lazy("7").root(), Ok(LazyValue::Scalar(JsonTypeNumber(Number::from(7)))));
+
+    // Reading carries on where an earlier iterator stopped
+    let mut first = document.children(a);
+    assert_eq!(first.next().unwrap().unwrap(), elements[0]);
+    let rest = document.children_from(a, first.position()).collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(rest, elements[1..]);
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(error_of(""), (EndOfInput, 0));
+    assert_eq!(error_of("[[1]"), (EndOfInput, 4));
+    assert_eq!(error_of("[1}"), (UnexpectedCharacter, 2));
+    assert_eq!(error_of("[] 1"), (TrailingCharacters, 3));
+
+    // Found when the container is read
+    let document = lazy("[1 2, 3]");
+    let LazyValue::Container(root) = document.root().unwrap() else { panic!() };
+    let children: Vec<_> = document.children(&root).map(|x| x.map_err(|x| (x.kind(), x.offset()))).collect();
+    assert_eq!(children, vec![Err((MissingComma, 3))]);
+}
+
+#[test]
+fn test_open_file() {
+    let path = std::env::temp_dir().join("json_editor_lazy_test.jsonc");
+    fs::write(&path, "// list\n[1, /* ] */ [2,],]").unwrap();
+    let options = ParseOptions::new().with_dialect(Dialect::Jsonc);
+    // Safety: nothing else writes to the file while it is open
+    let document = unsafe { LazyDocument::open(&path, &options) }.unwrap();
+    let LazyValue::Container(root) = document.root().unwrap() else { panic!() };
+    assert_eq!(root.len, 2);
+    assert_eq!(document.value(&root).unwrap(), JsonTypeArray(Array(vec![
+        JsonTypeNumber(Number::from(1)),
+        JsonTypeArray(Array(vec![JsonTypeNumber(Number::from(2))])),
+    ])));
+
+    fs::write(&path, "[1, 2").unwrap();
+    let error = unsafe { LazyDocument::open(&path, &options) }.err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+
+    // Indexing reports how far it has got, and stops once it is cancelled
+    fs::write(&path, "[1, 2]").unwrap();
+    let progress = Progress::new();
+    assert!(unsafe { LazyDocument::open_with_progress(&path, &options, &progress) }.is_ok());
+    assert_eq!((progress.done(), progress.total()), (6, 6));
+    progress.cancel();
+    let error = unsafe { LazyDocument::open_with_progress(&path, &options, &progress) }.err().unwrap();
+    assert_eq!(error.into_inner().unwrap().downcast::<Error>().unwrap().kind(), Cancelled);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_matches_full_parse() {
+    let json = fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/64KB.json")).unwrap();
+    let document = lazy(&json);
+    let LazyValue::Container(root) = document.root().unwrap() else { panic!() };
+    let value = to_object(&json).unwrap();
+    let JsonTypeArray(Array(values)) = &value else { panic!() };
+    assert_eq!(root.len, values.len());
+    for ((_, child), value) in document.children(&root).map(Result::unwrap).zip(values.iter()) {
+        let LazyValue::Container(child) = child else { panic!() };
+        assert_eq!(&document.value(&child).unwrap(), value);
+    }
+}
@@ -0,0 +1,51 @@
+use json_editor::json::error::ErrorKind;
+use json_editor::json::error::ErrorKind::{EndOfInput, InputTooLarge, TrailingCharacters};
+use json_editor::json::lines;
+use json_editor::json::lines::Lines;
+use json_editor::json::number::Number;
+use json_editor::json::options::{Dialect, ParseOptions};
+use json_editor::json::value::JsonValueType::{JsonTypeNumber, JsonTypeString};
+
+// The line numbers of the records, and line, column and kind of the errors
+fn summary(lines: Lines) -> Vec<Result<usize, (usize, usize, ErrorKind)>> {
+    lines.map(|x| x.map(|x| x.number).map_err(|x| (x.line(), x.column(), x.kind()))).collect()
+}
+
+#[test]
+fn test_records() {
+    let records: Vec<_> = lines("1\n\"two\"\r\n\n  3  \n").map(Result::unwrap).collect();
+    assert_eq!(records.iter().map(|x| (x.number, x.offset)).collect::<Vec<_>>(), vec![(1, 0), (2, 2), (4, 10)]);
+    assert_eq!(records[1].value, JsonTypeString("two".to_string()));
+    assert_eq!(records[2].value, JsonTypeNumber(Number::from(3)));
+    assert_eq!(lines("").count(), 0);
+    assert_eq!(lines("\n\n").count(), 0);
+}
+
+#[test]
+fn test_bad_lines() {
+    // A document has to end on its own line
+    let json = "{\"a\" : 1}\n{\"a\" :\n2}\n[1] [2]\n{\"a\" : 3}";
+    assert_eq!(summary(lines(json)), vec![
+        Ok(1),
+        Err((2, 7, EndOfInput)),
+        Err((3, 2, TrailingCharacters)),
+        Err((4, 5, TrailingCharacters)),
+        Ok(5),
+    ]);
+    let error = lines(json).nth(1).unwrap().unwrap_err();
+    assert_eq!(error.offset(), 16);
+    assert!(error.snippet().starts_with("2 | {\"a\" :"));
+
+    let good = lines(json).filter_map(Result::ok).map(|x| x.number).collect::<Vec<_>>();
+    assert_eq!(good, vec![1, 5]);
+}
+
+#[test]
+fn test_options() {
+    let options = ParseOptions::new().with_dialect(Dialect::Jsonc).with_max_input_size(8);
+    let json = "// header\n[1, 2,]\n[1, 2, 3, 4]\n";
+    let result: Vec<_> = Lines::new(json).with_options(&options).collect();
+    assert_eq!(result[0].as_ref().unwrap().number, 2);
+    let error = result[1].as_ref().unwrap_err();
+    assert_eq!((error.kind(), error.line(), error.offset()), (InputTooLarge, 3, 26));
+}
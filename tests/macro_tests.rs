@@ -0,0 +1,87 @@
+use indexmap::IndexMap;
+use json_editor::json;
+use json_editor::json::number::Number;
+use json_editor::json::value::JsonValueType::{JsonTypeArray, JsonTypeBool, JsonTypeNull, JsonTypeNumber, JsonTypeObject, JsonTypeString};
+use json_editor::json::{to_object, Array, Object};
+
+#[test]
+fn test_literals() {
+    assert_eq!(json!(null), JsonTypeNull);
+    assert_eq!(json!(true), JsonTypeBool(true));
+    assert_eq!(json!(-1.5), JsonTypeNumber(Number::from(-1.5)));
+    assert_eq!(json!("text"), JsonTypeString("text".to_string()));
+    assert_eq!(json!([]), JsonTypeArray(Array(Vec::new())));
+    assert_eq!(json!({}), JsonTypeObject(Object(IndexMap::new())));
+    assert_eq!(json!([null, false, 1,]), JsonTypeArray(Array(vec![JsonTypeNull, JsonTypeBool(false), JsonTypeNumber(Number::from(1))])));
+}
+
+#[test]
+fn test_nesting() {
+    let value = json!({
+        "name": "x",
+        "list": [1, [2, {}], {"a": null}],
+        "empty": [],
+        "nested": {"b": true, "c": {"d": [false]}},
+    });
+    let expected = to_object(r#"{"name" : "x", "list" : [1, [2, {}], {"a" : null}], "empty" : [], "nested" : {"b" : true, "c" : {"d" : [false]}}}"#);
+    assert_eq!(Ok(value.clone()), expected);
+    assert_eq!(value.as_object().unwrap().0.keys().collect::<Vec<_>>(), ["name", "list", "empty", "nested"]);
+}
+
+#[test]
+fn test_interpolation() {
+    let count = 3;
+    let names = vec!["a", "b"];
+    let key = String::from("dynamic");
+    let value = json!({
+        "count": count + 1,
+        "names": names.clone(),
+        "first": names[0],
+        "missing": None::<i32>,
+        (key): [count, count * 2],
+        ("literal".to_string() + "_key"): Some("set"),
+        "same": json!({"inner": count}),
+    });
+    assert_eq!(value.to_string(), r#"{"count" : 4, "names" : ["a", "b"], "first" : "a", "missing" : null, "dynamic" : [3, 6], "literal_key" : "set", "same" : {"inner" : 3}}"#);
+}
+
+#[test]
+fn test_repeated_keys() {
+    let value = json!({"a": 1, "b": 2, "a": 3});
+    assert_eq!(value.to_string(), r#"{"a" : 3, "b" : 2}"#);
+}
+
+#[test]
+fn test_many_members() {
+    // Values that are single tokens are put in place without munching
+    let value = json!({
+        "k0": 0, "k1": 1, "k2": 2, "k3": 3, "k4": 4, "k5": 5, "k6": 6, "k7": 7, "k8": 8, "k9": 9,
+        "k10": 10, "k11": 11, "k12": 12, "k13": 13, "k14": 14, "k15": 15, "k16": 16, "k17": 17, "k18": 18, "k19": 19,
+        "k20": 20, "k21": 21, "k22": 22, "k23": 23, "k24": 24, "k25": 25, "k26": 26, "k27": 27, "k28": 28, "k29": 29,
+        "k30": 30, "k31": 31, "k32": 32, "k33": 33, "k34": 34, "k35": 35, "k36": 36, "k37": 37, "k38": 38, "k39": 39,
+        "k40": 40, "k41": 41, "k42": 42, "k43": 43, "k44": 44, "k45": 45, "k46": 46, "k47": 47, "k48": 48, "k49": 49,
+        "k50": 50, "k51": 51, "k52": 52, "k53": 53, "k54": 54, "k55": 55, "k56": 56, "k57": 57, "k58": 58, "k59": 59,
+    });
+    assert_eq!(value.len(), Some(60));
+    assert_eq!(value["k59"], json!(59));
+
+    // Other values take a single step each
+    let value = json!({
+        "k0": -0, "k1": -1, "k2": -2, "k3": -3, "k4": -4, "k5": -5, "k6": -6, "k7": -7, "k8": -8, "k9": -9,
+        "k10": -10, "k11": -11, "k12": -12, "k13": -13, "k14": -14, "k15": -15, "k16": -16, "k17": -17, "k18": -18, "k19": -19,
+        "k20": -20, "k21": -21, "k22": -22, "k23": -23, "k24": -24, "k25": -25, "k26": -26, "k27": -27, "k28": -28, "k29": -29,
+        "k30": -30, "k31": -31, "k32": -32, "k33": -33, "k34": -34, "k35": -35, "k36": -36, "k37": -37, "k38": -38, "k39": -39,
+        "k40": -40, "k41": -41, "k42": -42, "k43": -43, "k44": -44, "k45": -45, "k46": -46, "k47": -47, "k48": -48, "k49": -49,
+        "k50": -50, "k51": -51, "k52": -52, "k53": -53, "k54": -54, "k55": -55, "k56": -56, "k57": -57, "k58": -58, "k59": -59,
+    });
+    assert_eq!(value.len(), Some(60));
+    assert_eq!(value["k59"], json!(-59));
+    assert_eq!(json!([
+        -0, -1, -2, -3, -4, -5, -6, -7, -8, -9,
+        -10, -11, -12, -13, -14, -15, -16, -17, -18, -19,
+        -20, -21, -22, -23, -24, -25, -26, -27, -28, -29,
+        -30, -31, -32, -33, -34, -35, -36, -37, -38, -39,
+        -40, -41, -42, -43, -44, -45, -46, -47, -48, -49,
+        -50, -51, -52, -53, -54, -55, -56, -57, -58, -59,
+    ]).len(), Some(60));
+}
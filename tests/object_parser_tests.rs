@@ -1,8 +1,11 @@
-use std::collections::HashMap;
-use json_editor::json::error::Error::EndOfLine;
-use json_editor::json::{Array, Object, to_object};
-use json_editor::json::value::JsonValueType;
-use json_editor::json::value::JsonValueType::{JsonTypeArray, JsonTypeBool, JsonTypeNumber, JsonTypeObject, JsonTypeString};
+use indexmap::IndexMap;
+use json_editor::json::error::ErrorKind::*;
+use json_editor::json::{Array, Object, to_object, to_object_bytes, to_objects};
+use json_editor::json::number::Number;
+use json_editor::json::value::JsonValueType::{JsonTypeArray, JsonTypeBool, JsonTypeNull, JsonTypeNumber, JsonTypeObject, JsonTypeString};
+
+mod common;
+use common::error_of;
 
 #[test]
 fn test_parse_bool() {
@@ -13,45 +16,157 @@ fn test_parse_bool() {
 fn test_parse_array() {
     assert_eq!(to_object("[]"), Ok(JsonTypeArray(Array(Vec::new()))));
     assert_eq!(to_object("[true,false]"), Ok(JsonTypeArray(Array(vec![JsonTypeBool(true), JsonTypeBool(false)]))));
-    assert_eq!(to_object("[[]"), Err(EndOfLine));
+    assert_eq!(error_of("[[]"), (EndOfInput, 3));
 }
 #[test]
 fn test_json_parse_string() {
     assert_eq!(to_object("\"\""), Ok(JsonTypeString(String::from(""))));
     assert_eq!(to_object("\"test\""), Ok(JsonTypeString(String::from("test"))));
-    assert_eq!(to_object("\"test"), Err(EndOfLine));
+    assert_eq!(error_of("\"test"), (UnterminatedString, 0));
     assert_eq!(to_object("\"t\\\"est\""), Ok(JsonTypeString(String::from("t\"est"))));
 }
 
+#[test]
+fn test_json_parse_string_escapes() {
+    assert_eq!(to_object(r#""\\ \/ \b \f \n \r \t""#), Ok(JsonTypeString(String::from("\\ / \u{8} \u{c} \n \r \t"))));
+    assert_eq!(to_object(r#""caf\u00e9""#), Ok(JsonTypeString(String::from("café"))));
+    assert_eq!(to_object(r#""\u00E9\u20ac""#), Ok(JsonTypeString(String::from("é€"))));
+    assert_eq!(to_object(r#""\ud83d\ude00""#), Ok(JsonTypeString(String::from("😀"))));
+    assert_eq!(to_object(r#""\\n""#), Ok(JsonTypeString(String::from("\\n"))));
+}
+
+#[test]
+fn test_json_parse_string_invalid() {
+    assert_eq!(error_of(r#""\x""#), (InvalidEscape, 2));
+    assert_eq!(error_of(r#""\u00g0""#), (InvalidEscape, 5));
+    assert_eq!(error_of(r#""\u00"#), (UnterminatedString, 0));
+    // Unpaired surrogates
+    assert_eq!(error_of(r#""\ude00""#), (InvalidEscape, 1));
+    assert_eq!(error_of(r#""\ud83dx""#), (InvalidEscape, 7));
+    assert_eq!(error_of(r#""\ud83d\u0041""#), (InvalidEscape, 7));
+    // Unescaped control characters
+    assert_eq!(error_of("\"a\nb\""), (ControlCharacter, 2));
+    assert_eq!(error_of("\"\t\""), (ControlCharacter, 1));
+}
+
 #[test]
 fn test_json_parse_number() {
-    assert_eq!(to_object("1"), Ok(JsonTypeNumber(1.0)));
-    assert_eq!(to_object("2.5"), Ok(JsonTypeNumber(2.5)));
-    assert_eq!(to_object("-5.0"), Ok(JsonTypeNumber(-5.0)));
+    assert_eq!(to_object("1"), Ok(JsonTypeNumber(Number::from(1))));
+    assert_eq!(to_object("2.5"), Ok(JsonTypeNumber(Number::from(2.5))));
+    assert_eq!(to_object("-5.0"), Ok(JsonTypeNumber(Number::from(-5))));
+    assert_eq!(to_object("0"), Ok(JsonTypeNumber(Number::from(0))));
+    assert_eq!(to_object("-0.25"), Ok(JsonTypeNumber(Number::from(-0.25))));
+    assert_eq!(to_object("1e3"), Ok(JsonTypeNumber(Number::from(1000))));
+    assert_eq!(to_object("2.5E-1"), Ok(JsonTypeNumber(Number::from(0.25))));
+    assert_eq!(to_object("1e+2"), Ok(JsonTypeNumber(Number::from(100))));
+    assert_eq!(to_object("[10,0]"), Ok(JsonTypeArray(Array(vec![JsonTypeNumber(Number::from(10)), JsonTypeNumber(Number::from(0))]))));
+}
+
+#[test]
+fn test_json_parse_number_precision() {
+    assert_eq!(to_object("9007199254740993").unwrap().as_u64(), Some(9007199254740993));
+    assert_eq!(to_object("18446744073709551615").unwrap().as_u64(), Some(u64::MAX));
+    assert_eq!(to_object("-9223372036854775808").unwrap().as_i64(), Some(i64::MIN));
+    assert_eq!(to_object("1.5").unwrap().as_i64(), None);
+    assert_eq!(to_object("1e3").unwrap().as_number(), Some(1000.0));
+}
+
+#[test]
+fn test_json_number_round_trip() {
+    for json in ["0", "-0", "12", "-7", "1.50", "1e3", "2.5E-1", "1.0", "123456789012345678901234567890"] {
+        assert_eq!(to_object(json).unwrap().to_string(), json);
+    }
+}
+
+#[test]
+fn test_json_parse_number_invalid() {
+    assert_eq!(error_of("01"), (InvalidNumber, 1));
+    assert_eq!(error_of("-01"), (InvalidNumber, 2));
+    assert_eq!(error_of("1."), (InvalidNumber, 2));
+    assert_eq!(error_of("[1.]"), (InvalidNumber, 3));
+    assert_eq!(error_of(".5"), (UnexpectedCharacter, 0));
+    assert_eq!(error_of("+1"), (UnexpectedCharacter, 0));
+    assert_eq!(error_of("1e"), (InvalidNumber, 2));
+    assert_eq!(error_of("1e+"), (InvalidNumber, 3));
+    assert_eq!(error_of("[1e+]"), (InvalidNumber, 4));
+    assert_eq!(error_of("-"), (InvalidNumber, 1));
+    assert_eq!(error_of("-x"), (InvalidNumber, 1));
+    assert_eq!(error_of("Infinity"), (UnexpectedCharacter, 0));
+    assert_eq!(error_of("-Infinity"), (InvalidNumber, 1));
 }
 
 #[test]
 fn test_json_parse_object() {
-    let pair_1 = ("one".to_string(), JsonTypeNumber(1.0));
-    let pair_2 = ("two".to_string(), JsonTypeNumber(2.0));
-    assert_eq!(to_object("{}"), Ok(JsonTypeObject(Object(HashMap::new()))));
-    assert_eq!(to_object("{\"one\" : 1}"), Ok(JsonTypeObject(Object(HashMap::from([pair_1.clone()])))));
-    assert_eq!(to_object("{\"one\" : 1, \"two\":2}"), Ok(JsonTypeObject(Object(HashMap::from([pair_1.clone(), pair_2.clone()])))));
+    let pair_1 = ("one".to_string(), JsonTypeNumber(Number::from(1)));
+    let pair_2 = ("two".to_string(), JsonTypeNumber(Number::from(2)));
+    assert_eq!(to_object("{}"), Ok(JsonTypeObject(Object(IndexMap::new()))));
+    assert_eq!(to_object("{\"one\" : 1}"), Ok(JsonTypeObject(Object(IndexMap::from([pair_1.clone()])))));
+    assert_eq!(to_object("{\"one\" : 1, \"two\":2}"), Ok(JsonTypeObject(Object(IndexMap::from([pair_1.clone(), pair_2.clone()])))));
+}
+
+#[test]
+fn test_json_parse_object_invalid() {
+    assert_eq!(error_of("{\"a\" 1}"), (MissingColon, 5));
+    assert_eq!(error_of("{\"a\" : 1 \"b\" : 2}"), (MissingComma, 9));
+    assert_eq!(error_of("{1 : 2}"), (ExpectedKey, 1));
+    assert_eq!(error_of("{\"a\" : 1,}"), (ExpectedKey, 9));
+    assert_eq!(error_of("{\"a\" : "), (EndOfInput, 7));
+    assert_eq!(error_of("[1 2]"), (MissingComma, 3));
+    assert_eq!(error_of("[tru]"), (UnexpectedCharacter, 4));
+    // Errors inside of values are passed on unchanged
+    assert_eq!(error_of("{\"a\" : [1, \"\\q\"]}"), (InvalidEscape, 13));
+}
+
+#[test]
+fn test_error_position() {
+    let error = to_object("{\n  \"name\" : \"é\",\n  \"id\" : 01\n}").unwrap_err();
+    assert_eq!(error.kind(), InvalidNumber);
+    assert_eq!(error.offset(), 29);
+    assert_eq!((error.line(), error.column()), (3, 11));
+    assert_eq!(error.snippet(), "3 |   \"id\" : 01\n  |           ^");
+    assert_eq!(error.to_string(), format!("invalid number at line 3, column 11, found '1', expected '.' or 'e' or end of number\n{}", error.snippet()));
+}
+
+#[test]
+fn test_parse_bytes() {
+    assert_eq!(to_object_bytes("[\"€\", 1]".as_bytes()), Ok(JsonTypeArray(Array(vec![JsonTypeString("€".to_string()), JsonTypeNumber(Number::from(1))]))));
+    // Offsets are byte positions, not character positions
+    let error = to_object_bytes("[\"€\" 1]".as_bytes()).unwrap_err();
+    assert_eq!((error.kind(), error.offset(), error.column()), (MissingComma, 7, 6));
+    // Invalid UTF-8 is only a problem inside of strings
+    let error = to_object_bytes(b"[\"ab\xff\"]").unwrap_err();
+    assert_eq!((error.kind(), error.offset()), (InvalidUtf8, 4));
+    assert_eq!(error_of("[\u{a0}1]"), (UnexpectedCharacter, 1));
+}
+
+#[test]
+fn test_trailing_characters() {
+    assert_eq!(error_of("1 2"), (TrailingCharacters, 2));
+    assert_eq!(error_of("{} xyz"), (TrailingCharacters, 3));
+    assert_eq!(error_of("true false"), (TrailingCharacters, 5));
+    assert_eq!(error_of("[]]"), (TrailingCharacters, 2));
+    assert_eq!(to_object(" null \n\t "), Ok(JsonTypeNull));
+}
+
+#[test]
+fn test_parse_multiple_documents() {
+    assert_eq!(to_objects(""), Ok(vec![]));
+    assert_eq!(to_objects(" 1 2\n"), Ok(vec![JsonTypeNumber(Number::from(1)), JsonTypeNumber(Number::from(2))]));
+    assert_eq!(to_objects("{}[]"), Ok(vec![JsonTypeObject(Object(IndexMap::new())), JsonTypeArray(Array(vec![]))]));
+    assert_eq!(to_objects("true x").map_err(|x| (x.kind(), x.offset())), Err((UnexpectedCharacter, 5)));
 }
 
 #[test]
 fn test_array_value_to_string() {
     {
-        let json = r#"[]
-"#;
+        let json = "[]";
 
         let converted = to_object(json).unwrap();
         let result = converted.to_string();
         assert_eq!(json, result);
     }
     {
-        let json = r#"["one", "two", "three"]
-"#;
+        let json = r#"["one", "two", "three"]"#;
 
         let converted = to_object(json).unwrap();
         let result = converted.to_string();
@@ -61,35 +176,39 @@ fn test_array_value_to_string() {
 #[test]
 fn test_object_value_to_string() {
     {
-        let json = r#"{}
-"#;
+        let json = "{}";
 
         let converted = to_object(json).unwrap();
         let result = converted.to_string();
         assert_eq!(json, result);
     }
-    // Fix later
-//     {
-//         let json = r#"{"three" : 1.1, "two" : "value", "one" : true}
-// "#;
-//         let expected = vec!["1.1", "value", "true"];
-//         let keys  = vec!["three", "two", "one"];
-//
-//         let converted = to_object(json).unwrap();
-//         let result = converted.to_string();
-//         // Can't check against string as ordering changes due to hash map
-//
-//         if let Some(obj) = match converted {
-//             JsonTypeObject(obj) => {Some(obj)},
-//             _ => None
-//         } {
-//             assert_eq!(obj.len(), 3)
-//
-//         } else {
-//             assert!(false)
-//         }
-//
-//     }
+    {
+        let json = r#"{"three" : 1.1, "two" : "value", "one" : true}"#;
+        let converted = to_object(json).unwrap();
+        let result = converted.to_string();
+        assert_eq!(json, result);
+    }
+}
+
+#[test]
+fn test_object_order() {
+    let Ok(JsonTypeObject(mut object)) = to_object(r#"{"b" : 1, "c" : 2, "a" : 3, "b" : 4}"#) else { panic!() };
+    let keys = |object: &Object| object.0.keys().cloned().collect::<Vec<_>>().join(" ");
+    assert_eq!(keys(&object), "b c a");
+    assert_eq!(object.0["b"], JsonTypeNumber(Number::from(4)));
+
+    assert!(object.move_key("a", 0));
+    assert_eq!(keys(&object), "a b c");
+    assert!(object.move_key("a", 10));
+    assert_eq!(keys(&object), "b c a");
+    assert!(!object.move_key("x", 0));
+
+    object.sort_keys();
+    assert_eq!(keys(&object), "a b c");
+    object.sort_by(|_, v1, _, v2| v2.as_number().partial_cmp(&v1.as_number()).unwrap());
+    assert_eq!(keys(&object), "b a c");
+    assert_eq!(object.remove("a"), Some(JsonTypeNumber(Number::from(3))));
+    assert_eq!(keys(&object), "b c");
 }
 
 
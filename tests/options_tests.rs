@@ -0,0 +1,101 @@
+use indexmap::IndexMap;
+use json_editor::json::error::ErrorKind::{ArrayTooLong, DepthLimitExceeded, DuplicateKey, InputTooLarge, StringTooLong, TooManyMembers};
+use json_editor::json::{Array, Object, to_object, to_object_recovering_with, to_object_with};
+use json_editor::json::options::{Dialect, DuplicateKeys, ParseOptions};
+use json_editor::json::value::JsonValueType;
+use json_editor::json::value::JsonValueType::{JsonTypeArray, JsonTypeObject};
+
+mod common;
+use common::{error_with, number};
+
+fn with_duplicate_keys(policy: DuplicateKeys) -> ParseOptions {
+    ParseOptions::new().with_duplicate_keys(policy)
+}
+
+fn object(key: &str, value: JsonValueType) -> JsonValueType {
+    JsonTypeObject(Object(IndexMap::from([(key.to_string(), value)])))
+}
+
+#[test]
+fn test_duplicate_keys() {
+    let json = r#"{"a" : 1, "a" : [2], "a" : 3}"#;
+    assert_eq!(to_object(json), Ok(object("a", number(3))));
+    assert_eq!(to_object_with(json, &with_duplicate_keys(DuplicateKeys::KeepLast)), Ok(object("a", number(3))));
+    assert_eq!(to_object_with(json, &with_duplicate_keys(DuplicateKeys::KeepFirst)), Ok(object("a", number(1))));
+    assert_eq!(to_object_with(json, &with_duplicate_keys(DuplicateKeys::KeepAll)), Ok(object("a", JsonTypeArray(Array(vec![
+        number(1), JsonTypeArray(Array(vec![number(2)])), number(3)
+    ])))));
+
+    let error = to_object_with(json, &with_duplicate_keys(DuplicateKeys::Error)).unwrap_err();
+    assert_eq!((error.kind(), error.offset()), (DuplicateKey, 10));
+}
+
+#[test]
+fn test_duplicate_keys_nested() {
+    // Keys only clash within the same object
+    let json = r#"{"a" : {"a" : 1}, "b" : {"a" : 2}}"#;
+    assert!(to_object_with(json, &with_duplicate_keys(DuplicateKeys::Error)).is_ok());
+    let json = r#"[{"a" : 1}, {"b" : 1, "b" : 2}]"#;
+    assert_eq!(to_object_with(json, &with_duplicate_keys(DuplicateKeys::Error)).unwrap_err().offset(), 22);
+}
+
+#[test]
+fn test_max_depth() {
+    let options = ParseOptions::new().with_max_depth(2);
+    assert!(to_object_with("[[1], {\"a\" : 1}]", &options).is_ok());
+    let error = to_object_with("[[1], {\"a\" : []}]", &options).unwrap_err();
+    assert_eq!((error.kind(), error.offset()), (DepthLimitExceeded, 13));
+
+    // The default limit keeps hostile input from going anywhere near the stack size
+    let deep = "[".repeat(100_000);
+    assert_eq!(to_object(&deep).unwrap_err().kind(), DepthLimitExceeded);
+}
+
+#[test]
+fn test_deep_values() {
+    // Neither parsing nor dropping may recurse once per level
+    let depth = 200_000;
+    let json = format!("{}null{}", "[{\"a\" : ".repeat(depth), "}]".repeat(depth));
+    let value = to_object_with(&json, &ParseOptions::new().with_max_depth(usize::MAX)).unwrap();
+    drop(value);
+}
+
+#[test]
+fn test_into_inner() {
+    // Arrays and objects implement Drop, their contents are taken out instead of moved out
+    let JsonTypeArray(array) = to_object(r#"[{"a" : 1}, 2]"#).unwrap() else { panic!() };
+    let mut values = array.into_vec();
+    assert_eq!(values.pop(), Some(number(2)));
+    let Some(JsonTypeObject(object)) = values.pop() else { panic!() };
+    assert_eq!(object.into_map(), IndexMap::from([("a".to_string(), number(1))]));
+}
+
+#[test]
+fn test_limits() {
+    let json = r#"{"list" : [1, 2, 3], "name" : "four", "x" : null}"#;
+    assert!(to_object_with(json, &ParseOptions::new()
+        .with_max_input_size(json.len())
+        .with_max_string_length(4)
+        .with_max_array_length(3)
+        .with_max_object_members(3)).is_ok());
+
+    assert_eq!(error_with(json, &ParseOptions::new().with_max_input_size(10)), (InputTooLarge, 10));
+    assert_eq!(error_with(json, &ParseOptions::new().with_max_string_length(3)), (StringTooLong, 1));
+    assert_eq!(error_with(json, &ParseOptions::new().with_max_array_length(2)), (ArrayTooLong, 17));
+    assert_eq!(error_with(json, &ParseOptions::new().with_max_object_members(2)), (TooManyMembers, 38));
+    let json5 = ParseOptions::new().with_dialect(Dialect::Json5).with_max_object_members(1);
+    assert_eq!(error_with("{a:1,b:2,c:3}", &json5), (TooManyMembers, 5));
+    assert_eq!(error_with("{'a':1,'b':2,'c':3}", &json5), (TooManyMembers, 7));
+
+    // Escapes count as the characters they stand for
+    assert!(to_object_with(r#""\u0041\u0042""#, &ParseOptions::new().with_max_string_length(2)).is_ok());
+    assert_eq!(error_with(r#"["ok", "a\nbcd"]"#, &ParseOptions::new().with_max_string_length(3)), (StringTooLong, 7));
+    assert_eq!(error_with(r#"{"a\nbcd" : 1}"#, &ParseOptions::new().with_max_string_length(3)), (StringTooLong, 1));
+
+    // When recovering, overlong strings are cut off at the limit
+    let recovered = to_object_recovering_with(r#"["abcdef", "a\nb€d", "€€"]"#, &ParseOptions::new().with_max_string_length(4));
+    assert_eq!(recovered.value.unwrap().to_string(), r#"["abcd", "a\nb", "€"]"#);
+    assert_eq!(recovered.diagnostics.iter().map(|x| (x.error.kind(), x.error.offset())).collect::<Vec<_>>(),
+               [(StringTooLong, 1), (StringTooLong, 11), (StringTooLong, 23)]);
+    assert_eq!(to_object("[]"), to_object_with("[]", &ParseOptions::new()));
+}
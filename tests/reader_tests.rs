@@ -0,0 +1,65 @@
+use json_editor::json::error::ErrorKind::{EndOfInput, MissingComma, TrailingCharacters};
+use json_editor::json::number::Number;
+use json_editor::json::reader::Event::{EndArray, EndObject, Key, StartArray, StartObject, Value};
+use json_editor::json::reader::{Event, Reader};
+use json_editor::json::value_ref::JsonValueRef::{JsonTypeBool, JsonTypeNull, JsonTypeNumber, JsonTypeString};
+
+// Every event with its offset and path
+fn events_of(json: &str) -> Vec<(Event<'_>, usize, String)> {
+    let mut reader = Reader::new(json);
+    let mut result = Vec::new();
+    while let Some(event) = reader.next() {
+        result.push((event.unwrap(), reader.offset(), reader.path().to_string()));
+    }
+    result
+}
+
+#[test]
+fn test_events() {
+    let json = r#"{"a" : [1, null], "b" : {}, "c" : "x"}"#;
+    assert_eq!(events_of(json), vec![
+        (StartObject, 0, "$".to_string()),
+        (Key("a".into()), 1, "$.a".to_string()),
+        (StartArray, 7, "$.a".to_string()),
+        (Value(JsonTypeNumber(Number::from(1))), 8, "$.a[0]".to_string()),
+        (Value(JsonTypeNull), 11, "$.a[1]".to_string()),
+        (EndArray, 15, "$.a".to_string()),
+        (Key("b".into()), 18, "$.b".to_string()),
+        (StartObject, 24, "$.b".to_string()),
+        (EndObject, 25, "$.b".to_string()),
+        (Key("c".into()), 28, "$.c".to_string()),
+        (Value(JsonTypeString("x".into())), 34, "$.c".to_string()),
+        (EndObject, 37, "$".to_string()),
+    ]);
+}
+
+#[test]
+fn test_events_scalar() {
+    assert_eq!(events_of(" true "), vec![(Value(JsonTypeBool(true)), 1, "$".to_string())]);
+    assert_eq!(events_of("[]"), vec![(StartArray, 0, "$".to_string()), (EndArray, 1, "$".to_string())]);
+}
+
+#[test]
+fn test_events_errors() {
+    let mut reader = Reader::new("[1 2]");
+    assert_eq!(reader.next(), Some(Ok(StartArray)));
+    assert_eq!(reader.next(), Some(Ok(Value(JsonTypeNumber(Number::from(1))))));
+    let error = reader.next().unwrap().unwrap_err();
+    assert_eq!((error.kind(), error.offset()), (MissingComma, 3));
+    assert_eq!(reader.next(), None);
+
+    assert_eq!(Reader::new("").next().unwrap().unwrap_err().kind(), EndOfInput);
+    assert_eq!(Reader::new("{\"a\" : 1").last().unwrap().unwrap_err().kind(), EndOfInput);
+    assert_eq!(Reader::new("1 2").last().unwrap().unwrap_err().kind(), TrailingCharacters);
+}
+
+#[test]
+fn test_skip_value() {
+    let mut reader = Reader::new(r#"{"big" : {"x" : [1, [2]]}, "id" : 7}"#);
+    assert_eq!(reader.next(), Some(Ok(StartObject)));
+    assert_eq!(reader.next(), Some(Ok(Key("big".into()))));
+    reader.skip_value().unwrap();
+    assert_eq!(reader.next(), Some(Ok(Key("id".into()))));
+    assert_eq!(reader.next(), Some(Ok(Value(JsonTypeNumber(Number::from(7))))));
+    assert_eq!(reader.path().to_string(), "$.id");
+}
@@ -0,0 +1,136 @@
+use indexmap::IndexMap;
+use json_editor::json::error::{ErrorKind, Severity};
+use json_editor::json::error::ErrorKind::*;
+use json_editor::json::{Array, Object, to_object_recovering, to_object_recovering_with_progress};
+use json_editor::json::options::ParseOptions;
+use json_editor::json::path::{Path, PathSegment};
+use json_editor::json::progress::{Progress, REPORT_INTERVAL};
+use json_editor::json::value::JsonValueType;
+use json_editor::json::value::JsonValueType::{JsonTypeArray, JsonTypeBool, JsonTypeNull, JsonTypeObject, JsonTypeString};
+
+mod common;
+use common::number;
+
+fn recover(json: &str) -> (Option<JsonValueType>, Vec<(ErrorKind, usize)>) {
+    let result = to_object_recovering(json);
+    let errors = result.diagnostics.iter().map(|x| (x.error.kind(), x.error.offset())).collect();
+    (result.value, errors)
+}
+
+#[test]
+fn test_recover_valid_document() {
+    assert_eq!(recover("[1, {\"a\" : true}]"), (Some(JsonTypeArray(Array(vec![
+        number(1),
+        JsonTypeObject(Object(IndexMap::from([("a".to_string(), JsonTypeBool(true))])))
+    ]))), vec![]));
+}
+
+#[test]
+fn test_recover_missing_comma() {
+    assert_eq!(recover("[1 2, 3]"), (Some(JsonTypeArray(Array(vec![number(1), number(2), number(3)]))), vec![(MissingComma, 3)]));
+    assert_eq!(recover("{\"a\" : 1 \"b\" : 2}"), (Some(JsonTypeObject(Object(IndexMap::from([
+        ("a".to_string(), number(1)),
+        ("b".to_string(), number(2))
+    ])))), vec![(MissingComma, 9)]));
+}
+
+#[test]
+fn test_recover_unclosed_containers() {
+    assert_eq!(recover("[1, [2"), (Some(JsonTypeArray(Array(vec![
+        number(1),
+        JsonTypeArray(Array(vec![number(2)]))
+    ]))), vec![(EndOfInput, 6)]));
+    assert_eq!(recover("{\"a\" : [1}"), (Some(JsonTypeObject(Object(IndexMap::from([
+        ("a".to_string(), JsonTypeArray(Array(vec![number(1)])))
+    ])))), vec![(MissingComma, 9)]));
+    assert_eq!(recover("{\"a\" : \"text\n, \"b\" : 2}"), (Some(JsonTypeObject(Object(IndexMap::from([
+        ("a".to_string(), JsonTypeString("text".to_string())),
+        ("b".to_string(), number(2))
+    ])))), vec![(UnterminatedString, 7)]));
+    assert_eq!(recover("{"), (Some(JsonTypeObject(Object(IndexMap::new()))), vec![(EndOfInput, 1)]));
+    assert_eq!(recover("{\"a\":1,"), (Some(JsonTypeObject(Object(IndexMap::from([
+        ("a".to_string(), number(1))
+    ])))), vec![(EndOfInput, 7)]));
+}
+
+#[test]
+fn test_recover_stray_characters() {
+    assert_eq!(recover("[1, x, 3]"), (Some(JsonTypeArray(Array(vec![number(1), JsonTypeNull, number(3)]))), vec![(UnexpectedCharacter, 4)]));
+    assert_eq!(recover("[1 ; 2]"), (Some(JsonTypeArray(Array(vec![number(1), number(2)]))), vec![(MissingComma, 3)]));
+    assert_eq!(recover("[1, 2,]"), (Some(JsonTypeArray(Array(vec![number(1), number(2)]))), vec![(UnexpectedCharacter, 6)]));
+    assert_eq!(recover("{name : 01, \"ok\" : tru}"), (Some(JsonTypeObject(Object(IndexMap::from([
+        ("name".to_string(), JsonTypeNull),
+        ("ok".to_string(), JsonTypeNull)
+    ])))), vec![(ExpectedKey, 1), (InvalidNumber, 9), (UnexpectedCharacter, 22)]));
+    assert_eq!(recover("true false"), (Some(JsonTypeBool(true)), vec![(TrailingCharacters, 5)]));
+}
+
+#[test]
+fn test_recover_broken_escapes() {
+    let empty = || Some(JsonTypeArray(Array(vec![JsonTypeString(String::new())])));
+    assert_eq!(recover(r#"["\u12"]"#), (empty(), vec![(InvalidEscape, 6)]));
+    assert_eq!(recover(r#"["\ud83d"]"#), (empty(), vec![(InvalidEscape, 8)]));
+    assert_eq!(recover(r#"["\u12x"]"#), (Some(JsonTypeArray(Array(vec![JsonTypeString("x".to_string())]))), vec![(InvalidEscape, 6)]));
+}
+
+#[test]
+fn test_recover_empty_input() {
+    assert_eq!(recover("  "), (None, vec![(EndOfInput, 2)]));
+}
+
+#[test]
+fn test_recover_diagnostic_path() {
+    let result = to_object_recovering("{\"servers\" : [{\"port\" : 80}, {\"port\" : 08}]}");
+    assert_eq!(result.diagnostics.len(), 1);
+    let diagnostic = &result.diagnostics[0];
+    assert_eq!(diagnostic.path, Path(vec![
+        PathSegment::Key("servers".to_string()),
+        PathSegment::Index(1),
+        PathSegment::Key("port".to_string())
+    ]));
+    assert_eq!(diagnostic.path.to_string(), "$.servers[1].port");
+    assert_eq!((diagnostic.error.line(), diagnostic.error.column()), (1, 41));
+}
+
+#[test]
+fn test_recover_duplicate_key() {
+    let result = to_object_recovering("{\"a\" : 1,\n \"a\" : 2}");
+    assert_eq!(result.value, Some(JsonTypeObject(Object(IndexMap::from([("a".to_string(), number(2))])))));
+    let diagnostic = &result.diagnostics[0];
+    assert_eq!((diagnostic.error.kind(), diagnostic.error.line(), diagnostic.severity), (DuplicateKey, 2, Severity::Warning));
+    assert_eq!(diagnostic.path, Path(vec![PathSegment::Key("a".to_string())]));
+    let first = diagnostic.related.unwrap();
+    assert_eq!((first.start, first.end, first.line, first.column), (1, 4, 1, 2));
+}
+
+#[test]
+fn test_recover_too_deep() {
+    let json = format!("[1, {}\"]\"{}, 2]", "[".repeat(200), "]".repeat(200));
+    let (value, errors) = recover(&json);
+    assert_eq!(errors, vec![(DepthLimitExceeded, 4 + 127)]);
+
+    // The innermost array that fits is kept with the skipped part as null, parsing goes on after it
+    let Some(JsonTypeArray(Array(outer))) = &value else { panic!() };
+    assert_eq!((&outer[0], &outer[2]), (&number(1), &number(2)));
+    let mut inner = &outer[1];
+    for _ in 0..126 {
+        let JsonTypeArray(Array(values)) = inner else { panic!() };
+        inner = &values[0];
+    }
+    assert_eq!(inner, &JsonTypeArray(Array(vec![JsonTypeNull])));
+}
+
+#[test]
+fn test_recover_with_progress() {
+    let json = format!("[{}1 2]", "0, ".repeat(REPORT_INTERVAL));
+    let progress = Progress::new();
+    let result = to_object_recovering_with_progress(&json, &ParseOptions::default(), &progress).unwrap();
+    assert_eq!(to_object_recovering(&json), result);
+    assert_eq!((progress.done(), progress.total(), progress.fraction()), (json.len(), json.len(), Some(1.0)));
+
+    // Stops at the first update after the cancel
+    let progress = Progress::new();
+    progress.cancel();
+    let error = to_object_recovering_with_progress(&json, &ParseOptions::default(), &progress).unwrap_err();
+    assert_eq!((error.kind(), error.offset()), (Cancelled, 0));
+}
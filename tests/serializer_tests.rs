@@ -0,0 +1,89 @@
+use std::io;
+use json_editor::json::number::Number;
+use json_editor::json::serializer::{to_string, to_string_with_comments, to_writer, Indent, SerializeOptions};
+use json_editor::json::value::JsonValueType::JsonTypeNumber;
+use json_editor::json::options::{Dialect, ParseOptions};
+use json_editor::json::{to_object, to_object_with, to_object_with_comments};
+
+const SOURCE: &str = r#"{"name" : "tab\there", "empty" : [], "list" : [1, 2.50, {"b" : null, "a" : true}], "": {}}"#;
+
+#[test]
+fn test_styles() {
+    let value = to_object(SOURCE).unwrap();
+    assert_eq!(to_string(&value, &SerializeOptions::compact()).unwrap(),
+               r#"{"name":"tab\there","empty":[],"list":[1,2.50,{"b":null,"a":true}],"":{}}"#);
+    assert_eq!(to_string(&value, &SerializeOptions::compact().with_sort_keys(true)).unwrap(),
+               r#"{"":{},"empty":[],"list":[1,2.50,{"a":true,"b":null}],"name":"tab\there"}"#);
+    assert_eq!(to_string(&value, &SerializeOptions::new().with_indent(Indent::Tab)).unwrap(),
+               "{\n\t\"name\": \"tab\\there\",\n\t\"empty\": [],\n\t\"list\": [\n\t\t1,\n\t\t2.50,\n\t\t{\n\t\t\t\"b\": null,\n\t\t\t\"a\": true\n\t\t}\n\t],\n\t\"\": {}\n}");
+
+    // Only the containers that fit in the width are kept on one line
+    let options = SerializeOptions::new().with_indent(Indent::Spaces(2)).with_line_width(30);
+    assert_eq!(to_string(&value, &options).unwrap(),
+               "{\n  \"name\": \"tab\\there\",\n  \"empty\": [],\n  \"list\": [\n    1,\n    2.50,\n    {\"b\": null, \"a\": true}\n  ],\n  \"\": {}\n}");
+    let options = options.with_line_width(200);
+    assert_eq!(to_string(&value, &options).unwrap(), r#"{"name": "tab\there", "empty": [], "list": [1, 2.50, {"b": null, "a": true}], "": {}}"#);
+
+    // Everything that is written reads back as the same value
+    for options in [SerializeOptions::new(), SerializeOptions::compact(), options] {
+        assert_eq!(to_object(&to_string(&value, &options).unwrap()).unwrap(), value);
+    }
+}
+
+#[test]
+fn test_escapes() {
+    let value = to_object(r#"["quote \" backslash \\ control \u0000 \u001f", "é 😀"]"#).unwrap();
+    assert_eq!(to_string(&value, &SerializeOptions::compact()).unwrap(), r#"["quote \" backslash \\ control \u0000 \u001f","é 😀"]"#);
+    assert_eq!(to_string(&value, &SerializeOptions::compact().with_ascii_only(true)).unwrap(),
+               r#"["quote \" backslash \\ control \u0000 \u001f","\u00e9 \ud83d\ude00"]"#);
+}
+
+#[test]
+fn test_numbers() {
+    let options = ParseOptions::new().with_dialect(Dialect::Json5);
+    let value = to_object_with("[0x1F, +1, .5, 5., 1e400, -0]", &options).unwrap();
+    assert_eq!(to_string(&value, &SerializeOptions::compact()).unwrap(), "[31,1,0.5,5.0,1e400,-0]");
+
+    for number in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        let error = to_string(&JsonTypeNumber(Number::from(number)), &SerializeOptions::new()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
+
+#[test]
+fn test_to_writer() {
+    // Large enough to be handed to the writer in several chunks
+    let value = to_object(&format!("[{}]", vec!["\"some text\""; 20000].join(", "))).unwrap();
+    let mut out = Vec::new();
+    to_writer(&mut out, &value, &SerializeOptions::new()).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), to_string(&value, &SerializeOptions::new()).unwrap());
+    assert!(to_writer(&mut [0u8; 16][..], &value, &SerializeOptions::new()).is_err());
+}
+
+#[test]
+fn test_comments() {
+    let options = ParseOptions::new().with_dialect(Dialect::Jsonc);
+    let (value, comments) = to_object_with_comments("// top\n{\"a\" : [1, 2], \"b\" : [3 /* three */], \"c\" : {} // c\n}", &options).unwrap();
+
+    // Containers with comments inside stay on several lines
+    let options = SerializeOptions::new().with_line_width(100);
+    assert_eq!(to_string_with_comments(&value, &comments, &options).unwrap(),
+               "// top\n{\n    \"a\": [1, 2],\n    \"b\": [\n        3 /* three */\n    ],\n    \"c\": {} // c\n}");
+    assert_eq!(to_string_with_comments(&value, &comments, &SerializeOptions::compact()).unwrap(), r#"{"a":[1,2],"b":[3],"c":{}}"#);
+
+    let value = JsonTypeNumber(Number::from(f64::NAN));
+    assert_eq!(to_string_with_comments(&value, &comments, &options).unwrap_err().kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_deep_values() {
+    // Writing may not recurse once per level either
+    let depth = 200_000;
+    let compact = format!("{}null{}", "[{\"a\":".repeat(depth), "}]".repeat(depth));
+    let value = to_object_with(&compact, &ParseOptions::new().with_max_depth(usize::MAX)).unwrap();
+    assert_eq!(to_string(&value, &SerializeOptions::compact()).unwrap(), compact);
+    assert_eq!(value.to_string(), compact.replace(":", " : "));
+
+    let options = SerializeOptions::new().with_line_width(usize::MAX);
+    assert_eq!(to_string(&value, &options).unwrap(), compact.replace(":", ": "));
+}
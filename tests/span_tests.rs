@@ -0,0 +1,58 @@
+use json_editor::json::{to_object_recovering, to_object_recovering_with, to_object_with_spans};
+use json_editor::json::options::{DuplicateKeys, ParseOptions};
+use json_editor::json::path::{Path, PathSegment};
+use json_editor::json::span::Span;
+
+mod common;
+use common::key;
+
+#[test]
+fn test_spans() {
+    let json = "{\n  \"name\" : \"x\",\n  \"list\" : [1, {\"a\" : null}]\n}";
+    let (_, spans) = to_object_with_spans(json).unwrap();
+    assert_eq!(spans.len(), 6);
+    assert_eq!(spans.get(&Path::root()).unwrap().value, Span { start: 0, end: json.len(), line: 1, column: 1 });
+
+    let name = spans.get(&Path(vec![key("name")])).unwrap();
+    assert_eq!(name.key, Some(Span { start: 4, end: 10, line: 2, column: 3 }));
+    assert_eq!(name.value, Span { start: 13, end: 16, line: 2, column: 12 });
+
+    let element = spans.get(&Path(vec![key("list"), PathSegment::Index(1)])).unwrap();
+    assert_eq!(&json[element.value.start..element.value.end], "{\"a\" : null}");
+    assert_eq!(element.key, None);
+    let null = spans.get(&Path(vec![key("list"), PathSegment::Index(1), key("a")])).unwrap();
+    assert_eq!(&json[null.value.start..null.value.end], "null");
+    assert_eq!((null.value.line, null.value.column), (3, 23));
+    assert_eq!(spans.find(null.value.start), Some(&Path(vec![key("list"), PathSegment::Index(1), key("a")])));
+}
+
+#[test]
+fn test_spans_repeated_key() {
+    let json = r#"{"a" : 1, "a" : 22}"#;
+    let (_, spans) = to_object_with_spans(json).unwrap();
+    let member = spans.get(&Path(vec![key("a")])).unwrap();
+    assert_eq!(&json[member.value.start..member.value.end], "22");
+}
+
+#[test]
+fn test_spans_keep_first() {
+    // The later member, and everything inside of it, is left out of the value and of the spans
+    let json = r#"{"a" : {"b" : 1}, "a" : {"b" : 22, "c" : 3}}"#;
+    let options = ParseOptions::new().with_duplicate_keys(DuplicateKeys::KeepFirst);
+    let recovered = to_object_recovering_with(json, &options);
+    assert_eq!(recovered.value.unwrap().to_string(), r#"{"a" : {"b" : 1}}"#);
+    let member = recovered.spans.get(&Path(vec![key("a")])).unwrap();
+    assert_eq!(&json[member.key.unwrap().start..member.value.end], r#""a" : {"b" : 1}"#);
+    let inner = recovered.spans.get(&Path(vec![key("a"), key("b")])).unwrap();
+    assert_eq!(inner.value.start, 14);
+    assert_eq!(recovered.spans.get(&Path(vec![key("a"), key("c")])), None);
+    assert_eq!(recovered.spans.len(), 3);
+}
+
+#[test]
+fn test_spans_recovering() {
+    let json = "[1,\n 2 3]";
+    let recovered = to_object_recovering(json);
+    let element = recovered.spans.get(&Path(vec![PathSegment::Index(2)])).unwrap();
+    assert_eq!((element.value.start, element.value.line, element.value.column), (7, 2, 4));
+}
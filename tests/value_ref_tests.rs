@@ -0,0 +1,34 @@
+use std::borrow::Cow;
+use json_editor::json::error::ErrorKind::TrailingCharacters;
+use json_editor::json::{to_object, to_object_ref};
+use json_editor::json::number::Number;
+use json_editor::json::value_ref::JsonValueRef;
+use json_editor::json::value_ref::JsonValueRef::{JsonTypeArray, JsonTypeBool, JsonTypeNumber, JsonTypeObject, JsonTypeString};
+
+#[test]
+fn test_parse_ref() {
+    let json = r#"{"name" : "plain", "escaped\n" : "café", "list" : [true, 1]}"#;
+    assert_eq!(to_object_ref(json), Ok(JsonTypeObject(vec![
+        (Cow::from("name"), JsonTypeString(Cow::from("plain"))),
+        (Cow::from("escaped\n"), JsonTypeString(Cow::from("café"))),
+        (Cow::from("list"), JsonTypeArray(vec![JsonTypeBool(true), JsonTypeNumber(Number::from(1))]))
+    ])));
+    assert_eq!(to_object_ref("[1] 2").unwrap_err().kind(), TrailingCharacters);
+}
+
+#[test]
+fn test_parse_ref_borrows() {
+    let json = r#"{"plain" : "text", "escaped" : "a\tb"}"#;
+    let value = to_object_ref(json).unwrap();
+    let members = value.as_object().unwrap();
+    assert!(matches!(members[0].0, Cow::Borrowed(_)));
+    assert!(matches!(value.get("plain"), Some(JsonTypeString(Cow::Borrowed(_)))));
+    assert!(matches!(value.get("escaped"), Some(JsonTypeString(Cow::Owned(_)))));
+}
+
+#[test]
+fn test_ref_into_owned() {
+    let json = r#"[{"a" : [null, "x", 2.50]}, false]"#;
+    let value: JsonValueRef = to_object_ref(json).unwrap();
+    assert_eq!(value.into_owned(), to_object(json).unwrap());
+}
@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+use indexmap::IndexMap;
+use json_editor::json::number::Number;
+use json_editor::json::path::PathSegment;
+use json_editor::json::value::JsonValueType;
+use json_editor::json::value::JsonValueType::{JsonTypeArray, JsonTypeNull, JsonTypeNumber, JsonTypeString};
+use json_editor::json::Array;
+
+#[test]
+fn test_display_and_parse() {
+    let value: JsonValueType = "{\"a\" : [1, \"x\\ny\"], \"b\" : {}}".parse().unwrap();
+    assert_eq!(value.to_string(), "{\"a\" : [1, \"x\\ny\"], \"b\" : {}}");
+    assert_eq!(format!("{:#}", value), "{\n    \"a\": [\n        1,\n        \"x\\ny\"\n    ],\n    \"b\": {}\n}");
+    assert_eq!(value.to_string().parse::<JsonValueType>().unwrap(), value);
+    assert!("[1,".parse::<JsonValueType>().is_err());
+}
+
+#[test]
+fn test_conversions() {
+    assert_eq!(JsonValueType::from(true), JsonValueType::JsonTypeBool(true));
+    assert_eq!(JsonValueType::from(-3), JsonTypeNumber(Number::from(-3)));
+    assert_eq!(JsonValueType::from(1.5), JsonTypeNumber(Number::from(1.5)));
+    assert_eq!(JsonValueType::from("abc".len()), JsonTypeNumber(Number::from(3)));
+    assert_eq!(JsonValueType::from("x"), JsonTypeString("x".to_string()));
+    assert_eq!(JsonValueType::from(None::<bool>), JsonTypeNull);
+    assert_eq!(JsonValueType::from(vec![1, 2]), JsonTypeArray(Array(vec![JsonValueType::from(1), JsonValueType::from(2)])));
+    assert_eq!(JsonValueType::from(HashMap::from([("a", 1)])).to_string(), "{\"a\" : 1}");
+
+    let object = JsonValueType::from(IndexMap::from([("z", vec![Some("s"), None]), ("a", vec![])]));
+    assert_eq!(object.to_string(), "{\"z\" : [\"s\", null], \"a\" : []}");
+    let collected: JsonValueType = (1..4).map(|x| x * 2).collect();
+    assert_eq!(collected.to_string(), "[2, 4, 6]");
+}
+
+#[test]
+fn test_access() {
+    let mut value = JsonValueType::default();
+    value["name"] = "first".into();
+    value["list"].push(1);
+    value["list"].push(JsonValueType::from("two"));
+    assert_eq!(value.insert("name", "second"), Some(JsonValueType::from("first")));
+    assert_eq!(value.to_string(), "{\"name\" : \"second\", \"list\" : [1, \"two\"]}");
+
+    assert_eq!(value["list"][1], JsonValueType::from("two"));
+    assert_eq!(value.get("list").and_then(|x| x.get(5)), None);
+    assert!(value["missing"][0]["deeper"].is_null());
+    assert_eq!(value.get(PathSegment::Key("name".to_string())), Some(&JsonValueType::from("second")));
+
+    *value.get_mut("list").unwrap().get_mut(0).unwrap() = JsonValueType::from(10);
+    value["list"][1] = JsonValueType::from(20);
+    let list = value["list"].take();
+    assert_eq!(list.as_array().map(|x| x.0.len()), Some(2));
+    assert_eq!(list[0].as_i64(), Some(10));
+    assert!(value["list"].is_null());
+    value.as_object_mut().unwrap().remove("list");
+    assert_eq!(value.as_object().map(|x| x.0.len()), Some(1));
+}
+
+#[test]
+#[should_panic(expected = "can not index a string with 0")]
+fn test_index_mut_mismatch() {
+    let mut value = JsonValueType::from("text");
+    value[0] = JsonTypeNull;
+}
+
+#[test]
+#[should_panic(expected = "index 2 is out of range for an array of length 1")]
+fn test_index_mut_out_of_range() {
+    let mut value = JsonValueType::from(vec![1]);
+    value[2] = JsonTypeNull;
+}